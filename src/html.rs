@@ -0,0 +1,776 @@
+use std::{collections::HashMap, fmt::Write};
+
+use crate::tree::{plain_text, walk_with_depth, AlertType, Alignment, HeadingEntry, Node};
+
+/// Options that change how the tree is rendered to HTML.
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Gives each header an `id` and wraps its content in a link to itself.
+    pub header_anchors: bool,
+    /// Wraps paragraphs in `<p>`. When disabled, only their content is written.
+    pub wrap_paragraphs: bool,
+    /// Renders a disabled checkbox at the start of each task list item.
+    pub task_list_checkboxes: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            header_anchors: false,
+            wrap_paragraphs: true,
+            task_list_checkboxes: false,
+        }
+    }
+}
+
+/// Renders the given tree as an HTML string.
+///
+/// Consecutive list items of the same kind are wrapped in a single `<ul>` or `<ol>`,
+/// and nested lists are rendered inside their parent `<li>`.
+/// Text is escaped, so the output is safe to embed in a page.
+/// Footnotes are numbered in the order they are first referenced, and their
+/// definitions are listed at the end with a `↩` link back to each reference.
+pub fn render_html(nodes: &[Node]) -> String {
+    render_html_with_options(nodes, &RenderOptions::default())
+}
+
+/// Renders the given tree as an HTML string with the given options.
+pub fn render_html_with_options(nodes: &[Node], options: &RenderOptions) -> String {
+    let mut footnote_defs = HashMap::new();
+    collect_footnote_defs(nodes, &mut footnote_defs);
+    let mut renderer = HtmlRenderer {
+        options,
+        slugs: HashMap::new(),
+        footnote_defs,
+        footnotes: vec![],
+        html: String::new(),
+    };
+    renderer.render_nodes(nodes);
+    renderer.render_footnotes();
+    renderer.html
+}
+
+/// Renders a single node as an HTML string, so the HTML of each block can be cached.
+///
+/// An `UnorderedList` or `OrderedList` item is wrapped in its own `<ul>` or `<ol>`, with its
+/// start number. A grouped `ListItem` does not know whether its `List` is ordered, so it
+/// renders as a bare `<li>`, to be wrapped by the caller.
+pub fn render_node_html(node: &Node) -> String {
+    render_html(std::slice::from_ref(node))
+}
+
+/// Maps the id of every footnote definition in the tree to its content.
+fn collect_footnote_defs<'a>(nodes: &'a [Node], defs: &mut HashMap<&'a str, &'a [Node]>) {
+    for node in nodes {
+        if let Node::FootnoteDef(footnote_def) = node {
+            defs.entry(&footnote_def.id).or_insert(&footnote_def.nodes);
+        }
+        for nested in node.nested_slices() {
+            collect_footnote_defs(nested, defs);
+        }
+    }
+}
+
+/// Converts header text to an anchor name, like `Hello, World!` to `hello-world`.
+///
+/// Letters are lowercased, spaces and hyphens become `-`, and other punctuation is dropped.
+pub fn slug(text: &str) -> String {
+    text.trim()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' | '-' => Some('-'),
+            c if c.is_alphanumeric() || c == '_' => Some(c),
+            _ => None,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Returns the slug of the text, with a `-1`, `-2`, ... suffix if it was already used.
+///
+/// `slugs` counts how many times each slug has been used so far.
+pub(crate) fn unique_slug(slugs: &mut HashMap<String, usize>, text: &str) -> String {
+    let base = slug(text);
+    let count = slugs.entry(base.clone()).or_insert(0);
+    let anchor = match *count {
+        0 => base,
+        n => format!("{}-{}", base, n),
+    };
+    *count += 1;
+    anchor
+}
+
+/// Returns the anchor of every header, mapped to the line the header starts at.
+///
+/// The anchors are the ids that `RenderOptions::header_anchors` gives the headers,
+/// so a repeated header text gets the `-1`, `-2`, ... suffixed anchor here as well.
+pub fn anchor_map(nodes: &[Node]) -> HashMap<String, usize> {
+    let mut slugs = HashMap::new();
+    let mut anchors = HashMap::new();
+    walk_with_depth(nodes, |_, node| {
+        if let Node::Header(header) = node {
+            let anchor = unique_slug(&mut slugs, &plain_text(&header.nodes));
+            anchors.insert(anchor, header.position.start as usize);
+        }
+    });
+    anchors
+}
+
+struct HtmlRenderer<'a> {
+    options: &'a RenderOptions,
+    slugs: HashMap<String, usize>, // how many times each header slug has been used
+    footnote_defs: HashMap<&'a str, &'a [Node]>,
+    footnotes: Vec<(String, usize)>, // referenced footnote ids in order, with their reference count
+    html: String,
+}
+
+impl HtmlRenderer<'_> {
+    fn render_nodes(&mut self, nodes: &[Node]) {
+        let mut ix = 0;
+        while ix < nodes.len() {
+            match &nodes[ix] {
+                Node::UnorderedList(_) | Node::OrderedList(_) => {
+                    // Group the sibling items into a single list
+                    let kind = nodes[ix].kind();
+                    let len = nodes[ix..]
+                        .iter()
+                        .take_while(|node| node.kind() == kind)
+                        .count();
+                    self.render_list(&nodes[ix..ix + len]);
+                    ix += len;
+                }
+                node => {
+                    self.render_node(node);
+                    ix += 1;
+                }
+            }
+        }
+    }
+
+    fn render_node(&mut self, node: &Node) {
+        match node {
+            Node::Header(header) => {
+                if self.options.header_anchors {
+                    let anchor = unique_slug(&mut self.slugs, &plain_text(&header.nodes));
+                    let _ = write!(
+                        self.html,
+                        "<h{} id=\"{}\"><a href=\"#{}\">",
+                        header.level, anchor, anchor
+                    );
+                    self.render_nodes(&header.nodes);
+                    let _ = write!(self.html, "</a></h{}>", header.level);
+                } else {
+                    let _ = write!(self.html, "<h{}>", header.level);
+                    self.render_nodes(&header.nodes);
+                    let _ = write!(self.html, "</h{}>", header.level);
+                }
+            }
+            Node::Paragraph(paragraph) => self.render_paragraph(&paragraph.nodes),
+            Node::UnorderedList(_) | Node::OrderedList(_) => {
+                self.render_list(std::slice::from_ref(node));
+            }
+            Node::List(list) => {
+                let close_tag = match (list.ordered, list.start) {
+                    (true, 1) => {
+                        self.html.push_str("<ol>");
+                        "</ol>"
+                    }
+                    (true, start) => {
+                        let _ = write!(self.html, "<ol start=\"{}\">", start);
+                        "</ol>"
+                    }
+                    (false, _) => {
+                        self.html.push_str("<ul>");
+                        "</ul>"
+                    }
+                };
+                self.render_nodes(&list.items);
+                self.html.push_str(close_tag);
+            }
+            Node::ListItem(list_item) => {
+                self.render_list_item(list_item.checked, &list_item.nodes, &list_item.children);
+            }
+            Node::CodeBlock(code_block) => {
+                match &code_block.language {
+                    Some(language) => {
+                        self.html.push_str("<pre><code class=\"language-");
+                        escape(language, &mut self.html);
+                        self.html.push_str("\">");
+                    }
+                    None => self.html.push_str("<pre><code>"),
+                }
+                escape(&code_block.value, &mut self.html);
+                self.html.push_str("</code></pre>");
+            }
+            // Front matter is metadata for other tools, not part of the page
+            Node::FrontMatter(_) => {}
+            Node::Table(table) => {
+                self.html.push_str("<table><thead><tr>");
+                for (cell, alignment) in table.headers.iter().zip(&table.alignments) {
+                    self.render_table_cell("th", *alignment, cell);
+                }
+                self.html.push_str("</tr></thead>");
+                if !table.rows.is_empty() {
+                    self.html.push_str("<tbody>");
+                    for row in &table.rows {
+                        self.html.push_str("<tr>");
+                        for (cell, alignment) in row.iter().zip(&table.alignments) {
+                            self.render_table_cell("td", *alignment, cell);
+                        }
+                        self.html.push_str("</tr>");
+                    }
+                    self.html.push_str("</tbody>");
+                }
+                self.html.push_str("</table>");
+            }
+            // Definitions are listed at the end, in the order they are referenced
+            Node::FootnoteDef(_) => {}
+            Node::TableOfContents(table_of_contents) => {
+                self.html.push_str("<nav class=\"table-of-contents\">");
+                self.render_table_of_contents(&table_of_contents.entries);
+                self.html.push_str("</nav>");
+            }
+            Node::Alert(alert) => {
+                let class = match alert.alert_type {
+                    AlertType::Note => "note",
+                    AlertType::Tip => "tip",
+                    AlertType::Important => "important",
+                    AlertType::Warning => "warning",
+                    AlertType::Caution => "caution",
+                };
+                let _ = write!(
+                    self.html,
+                    "<div class=\"markdown-alert markdown-alert-{}\">",
+                    class
+                );
+                self.render_nodes(&alert.nodes);
+                self.html.push_str("</div>");
+            }
+            Node::Text(text) => escape(&text.value, &mut self.html),
+            Node::Italic(italic) => {
+                self.html.push_str("<em>");
+                self.render_nodes(&italic.nodes);
+                self.html.push_str("</em>");
+            }
+            Node::Bold(bold) => {
+                self.html.push_str("<strong>");
+                self.render_nodes(&bold.nodes);
+                self.html.push_str("</strong>");
+            }
+            Node::Strikethrough(strikethrough) => {
+                self.html.push_str("<del>");
+                self.render_nodes(&strikethrough.nodes);
+                self.html.push_str("</del>");
+            }
+            Node::InlineCode(inline_code) => {
+                self.html.push_str("<code>");
+                escape(&inline_code.value, &mut self.html);
+                self.html.push_str("</code>");
+            }
+            Node::Link(link) => {
+                self.html.push_str("<a href=\"");
+                escape(&link.url, &mut self.html);
+                self.html.push('"');
+                if let Some(title) = &link.title {
+                    self.html.push_str(" title=\"");
+                    escape(title, &mut self.html);
+                    self.html.push('"');
+                }
+                self.html.push('>');
+                self.render_nodes(&link.text);
+                self.html.push_str("</a>");
+            }
+            Node::Image(image) => {
+                self.html.push_str("<img src=\"");
+                escape(&image.src, &mut self.html);
+                self.html.push_str("\" alt=\"");
+                escape(&image.alt, &mut self.html);
+                self.html.push('"');
+                if let Some(title) = &image.title {
+                    self.html.push_str(" title=\"");
+                    escape(title, &mut self.html);
+                    self.html.push('"');
+                }
+                self.html.push_str(" />");
+            }
+            Node::FootnoteRef(footnote_ref) => {
+                let id = footnote_ref.id.as_str();
+                let number = match self.footnotes.iter().position(|(used, _)| used == id) {
+                    Some(ix) => {
+                        self.footnotes[ix].1 += 1;
+                        ix + 1
+                    }
+                    None => {
+                        self.footnotes.push((id.to_string(), 1));
+                        self.footnotes.len()
+                    }
+                };
+                let count = self.footnotes[number - 1].1;
+                self.html.push_str("<sup id=\"");
+                write_footnote_ref_id(id, count, &mut self.html);
+                self.html.push_str("\"><a href=\"#fn-");
+                escape(id, &mut self.html);
+                let _ = write!(self.html, "\">{}</a></sup>", number);
+            }
+            Node::Whitespace(_) => self.html.push(' '),
+            Node::HardBreak(_) => self.html.push_str("<br />\n"),
+            Node::Eol(_) => self.html.push('\n'),
+        }
+    }
+
+    /// Renders a table cell, with an `align` attribute unless the column has no alignment.
+    fn render_table_cell(&mut self, tag: &str, alignment: Alignment, nodes: &[Node]) {
+        let _ = write!(self.html, "<{}", tag);
+        let align = match alignment {
+            Alignment::None => None,
+            Alignment::Left => Some("left"),
+            Alignment::Center => Some("center"),
+            Alignment::Right => Some("right"),
+        };
+        if let Some(align) = align {
+            let _ = write!(self.html, " align=\"{}\"", align);
+        }
+        self.html.push('>');
+        self.render_nodes(nodes);
+        let _ = write!(self.html, "</{}>", tag);
+    }
+
+    /// Renders the referenced footnote definitions as a numbered list, each with links back to its references.
+    fn render_footnotes(&mut self) {
+        if self.footnotes.is_empty() {
+            return;
+        }
+        self.html.push_str("<section class=\"footnotes\"><ol>");
+        // A definition can reference another footnote, which is then appended to the list
+        let mut ix = 0;
+        while ix < self.footnotes.len() {
+            let id = self.footnotes[ix].0.clone();
+            self.html.push_str("<li id=\"fn-");
+            escape(&id, &mut self.html);
+            self.html.push_str("\">");
+            if let Some(nodes) = self.footnote_defs.get(id.as_str()).copied() {
+                self.render_nodes(nodes);
+            }
+            for count in 1..=self.footnotes[ix].1 {
+                self.html.push_str(" <a href=\"#");
+                write_footnote_ref_id(&id, count, &mut self.html);
+                self.html.push_str("\" class=\"footnote-backref\">↩");
+                if count > 1 {
+                    let _ = write!(self.html, "<sup>{}</sup>", count);
+                }
+                self.html.push_str("</a>");
+            }
+            self.html.push_str("</li>");
+            ix += 1;
+        }
+        self.html.push_str("</ol></section>");
+    }
+
+    /// Renders sibling list items of the same kind as one list.
+    fn render_list(&mut self, items: &[Node]) {
+        let close_tag = match items.first() {
+            Some(Node::OrderedList(list)) if list.start != 1 => {
+                let _ = write!(self.html, "<ol start=\"{}\">", list.start);
+                "</ol>"
+            }
+            Some(Node::OrderedList(_)) => {
+                self.html.push_str("<ol>");
+                "</ol>"
+            }
+            _ => {
+                self.html.push_str("<ul>");
+                "</ul>"
+            }
+        };
+
+        for item in items {
+            let checked = match item {
+                Node::UnorderedList(list) => list.checked,
+                _ => None,
+            };
+            let (nodes, children) = item.nested();
+            self.render_list_item(checked, nodes, children);
+        }
+
+        self.html.push_str(close_tag);
+    }
+
+    fn render_list_item(&mut self, checked: Option<bool>, nodes: &[Node], children: &[Node]) {
+        self.html.push_str("<li>");
+        match checked.filter(|_| self.options.task_list_checkboxes) {
+            Some(true) => self
+                .html
+                .push_str("<input type=\"checkbox\" checked disabled /> "),
+            Some(false) => self.html.push_str("<input type=\"checkbox\" disabled /> "),
+            None => {}
+        }
+        // In a loose item, the text before a continuation paragraph is a paragraph too
+        match nodes
+            .iter()
+            .position(|node| matches!(node, Node::Paragraph(_)))
+        {
+            Some(ix) if ix > 0 => {
+                self.render_paragraph(&nodes[..ix]);
+                self.render_nodes(&nodes[ix..]);
+            }
+            _ => self.render_nodes(nodes),
+        }
+        self.render_nodes(children);
+        self.html.push_str("</li>");
+    }
+
+    /// Renders the nodes of a paragraph, wrapped in `<p>` unless `wrap_paragraphs` is off.
+    fn render_paragraph(&mut self, nodes: &[Node]) {
+        if self.options.wrap_paragraphs {
+            self.html.push_str("<p>");
+            self.render_nodes(nodes);
+            self.html.push_str("</p>");
+        } else {
+            self.render_nodes(nodes);
+        }
+    }
+
+    /// Renders the entries as nested lists of links, one level of nesting per header level.
+    fn render_table_of_contents(&mut self, entries: &[HeadingEntry]) {
+        let mut open_levels: Vec<usize> = vec![];
+        for entry in entries {
+            while open_levels.last().is_some_and(|&level| level > entry.level) {
+                self.html.push_str("</li></ul>");
+                open_levels.pop();
+            }
+            match open_levels.last() {
+                Some(&level) if level == entry.level => self.html.push_str("</li>"),
+                _ => {
+                    self.html.push_str("<ul>");
+                    open_levels.push(entry.level);
+                }
+            }
+            self.html.push_str("<li><a href=\"#");
+            escape(&entry.anchor, &mut self.html);
+            self.html.push_str("\">");
+            escape(&entry.text, &mut self.html);
+            self.html.push_str("</a>");
+        }
+        for _ in open_levels {
+            self.html.push_str("</li></ul>");
+        }
+    }
+}
+
+/// Writes the id of a footnote reference, with a `-2`, `-3`, ... suffix for repeated references.
+fn write_footnote_ref_id(id: &str, count: usize, html: &mut String) {
+    html.push_str("fnref-");
+    escape(id, html);
+    if count > 1 {
+        let _ = write!(html, "-{}", count);
+    }
+}
+
+/// Writes the text with the HTML special characters escaped.
+fn escape(text: &str, html: &mut String) {
+    for c in text.chars() {
+        match c {
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '&' => html.push_str("&amp;"),
+            '"' => html.push_str("&quot;"),
+            _ => html.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{build_tree, build_tree_grouped, Parser};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_render_headers() {
+        let input = "# Title\n### Sub *title*";
+        let html = render_html(&build_tree(input));
+
+        assert_eq!(html, "<h1>Title</h1><h3>Sub <em>title</em></h3>");
+    }
+
+    #[test]
+    fn test_render_nested_list() {
+        let input = "- item 1\n - item 1.1\n  - item 1.1.1\n- item 2";
+        let html = render_html(&build_tree(input));
+
+        assert_eq!(
+            html,
+            "<ul><li>item 1<ul><li>item 1.1<ul><li>item 1.1.1</li></ul></li></ul></li><li>item 2</li></ul>"
+        );
+    }
+
+    #[test]
+    fn test_render_single_node() {
+        let nodes = build_tree("Some *text*\n\n- item 1\n - item 1.1\n- item 2");
+
+        assert_eq!(render_node_html(&nodes[0]), "<p>Some <em>text</em></p>");
+        let list = nodes
+            .iter()
+            .find(|node| matches!(node, Node::UnorderedList(_)))
+            .unwrap();
+        assert_eq!(
+            render_node_html(list),
+            "<ul><li>item 1<ul><li>item 1.1</li></ul></li></ul>"
+        );
+
+        let nodes = build_tree("3. three\n4. four");
+        assert_eq!(
+            render_node_html(&nodes[1]),
+            "<ol start=\"4\"><li>four</li></ol>"
+        );
+
+        let Node::List(list) = &build_tree_grouped("1. one\n2. two")[0] else {
+            panic!("expected a list");
+        };
+        assert_eq!(render_node_html(&list.items[1]), "<li>two</li>");
+    }
+
+    #[test]
+    fn test_render_ordered_list() {
+        let input = "3. three\n4. four";
+        let html = render_html(&build_tree(input));
+
+        assert_eq!(html, "<ol start=\"3\"><li>three</li><li>four</li></ol>");
+    }
+
+    #[test]
+    fn test_render_ordered_sublists_restart_numbering() {
+        let input = "- a\n 1. x\n 2. y\n- b\n 1. z\n 2. w";
+        let html = render_html(&build_tree(input));
+
+        assert_eq!(
+            html,
+            "<ul><li>a<ol><li>x</li><li>y</li></ol></li><li>b<ol><li>z</li><li>w</li></ol></li></ul>"
+        );
+    }
+
+    #[test]
+    fn test_render_loose_list_item() {
+        let input = "- a\n\n  more text\n- b";
+        let html = render_html(&build_tree(input));
+
+        assert_eq!(html, "<ul><li><p>a</p><p>more text</p></li><li>b</li></ul>");
+    }
+
+    #[test]
+    fn test_render_mixed_emphasis() {
+        let input = "**bold** and *italic* with `code`\n\nnext";
+        let html = render_html(&build_tree(input));
+
+        assert_eq!(
+            html,
+            "<p><strong>bold</strong> and <em>italic</em> with <code>code</code></p>\n<p>next</p>"
+        );
+    }
+
+    #[test]
+    fn test_render_escaped_text() {
+        let input = "<a href=\"x\">&</a>";
+        let html = render_html(&build_tree(input));
+
+        assert_eq!(html, "<p>&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;</p>");
+    }
+
+    #[test]
+    fn test_render_escaped_code_block() {
+        let input = "```html\n<div>&amp;\n```";
+        let html = render_html(&build_tree(input));
+
+        assert_eq!(
+            html,
+            "<pre><code class=\"language-html\">&lt;div&gt;&amp;amp;</code></pre>"
+        );
+    }
+
+    #[test]
+    fn test_render_code_block_keeps_tabs() {
+        let input = "```\n\tif x {\n\t\treturn;\n\t}\n```";
+        let html = render_html(&build_tree(input));
+
+        assert_eq!(html, "<pre><code>\tif x {\n\t\treturn;\n\t}</code></pre>");
+    }
+
+    #[test]
+    fn test_render_hard_break() {
+        let input = "first  \nsecond";
+        let html = render_html(&build_tree(input));
+
+        assert_eq!(html, "<p>first<br />\nsecond</p>");
+    }
+
+    #[test]
+    fn test_render_footnote() {
+        let input = "see[^1]\n[^1]: the note";
+        let html = render_html(&build_tree(input));
+
+        assert_eq!(
+            html,
+            "<p>see<sup id=\"fnref-1\"><a href=\"#fn-1\">1</a></sup></p>\
+             <section class=\"footnotes\"><ol>\
+             <li id=\"fn-1\">the note <a href=\"#fnref-1\" class=\"footnote-backref\">↩</a></li>\
+             </ol></section>"
+        );
+    }
+
+    #[test]
+    fn test_render_footnotes_in_reference_order() {
+        let input = "[^a]: first\n[^b]: second\nsee[^b] and[^a], again[^b]";
+        let html = render_html(&build_tree(input));
+
+        assert_eq!(
+            html,
+            "<p>see<sup id=\"fnref-b\"><a href=\"#fn-b\">1</a></sup> \
+             and<sup id=\"fnref-a\"><a href=\"#fn-a\">2</a></sup>, \
+             again<sup id=\"fnref-b-2\"><a href=\"#fn-b\">1</a></sup></p>\
+             <section class=\"footnotes\"><ol>\
+             <li id=\"fn-b\">second \
+             <a href=\"#fnref-b\" class=\"footnote-backref\">↩</a> \
+             <a href=\"#fnref-b-2\" class=\"footnote-backref\">↩<sup>2</sup></a></li>\
+             <li id=\"fn-a\">first <a href=\"#fnref-a\" class=\"footnote-backref\">↩</a></li>\
+             </ol></section>"
+        );
+    }
+
+    #[test]
+    fn test_render_table() {
+        let input = "| Name | Score |\n| :-- | --: |\n| a | *1* |\n| b |";
+        let html = render_html(&build_tree(input));
+
+        assert_eq!(
+            html,
+            "<table><thead><tr><th align=\"left\">Name</th><th align=\"right\">Score</th></tr></thead>\
+             <tbody><tr><td align=\"left\">a</td><td align=\"right\"><em>1</em></td></tr>\
+             <tr><td align=\"left\">b</td><td align=\"right\"></td></tr></tbody></table>"
+        );
+    }
+
+    #[test]
+    fn test_render_table_alignments() {
+        let input = "| Left | Center | Right |\n| :-- | :-: | --: |\n| a | b | c |";
+        let html = render_html(&build_tree(input));
+
+        assert_eq!(
+            html,
+            "<table><thead><tr><th align=\"left\">Left</th><th align=\"center\">Center</th>\
+             <th align=\"right\">Right</th></tr></thead>\
+             <tbody><tr><td align=\"left\">a</td><td align=\"center\">b</td>\
+             <td align=\"right\">c</td></tr></tbody></table>"
+        );
+    }
+
+    #[test]
+    fn test_render_task_list_items() {
+        let nodes = build_tree("- [ ] todo\n- [x] done\n- plain");
+
+        assert_eq!(
+            render_html(&nodes),
+            "<ul><li>todo</li><li>done</li><li>plain</li></ul>"
+        );
+    }
+
+    #[test]
+    fn test_render_task_list_checkboxes() {
+        let nodes = build_tree("- [ ] todo\n- [x] done\n- plain");
+        let options = RenderOptions {
+            task_list_checkboxes: true,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            render_html_with_options(&nodes, &options),
+            "<ul><li><input type=\"checkbox\" disabled /> todo</li>\
+             <li><input type=\"checkbox\" checked disabled /> done</li><li>plain</li></ul>"
+        );
+    }
+
+    #[test]
+    fn test_render_link() {
+        let input = "see [the *docs*](https://example.com \"Docs\")";
+        let html = render_html(&build_tree(input));
+
+        assert_eq!(
+            html,
+            "<p>see <a href=\"https://example.com\" title=\"Docs\">the <em>docs</em></a></p>"
+        );
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("Hello, World!"), "hello-world");
+        assert_eq!(slug("  Getting *started*  "), "getting-started");
+    }
+
+    #[test]
+    fn test_anchor_map() {
+        let input = "# Setup\ntext\n## Setup\n## Usage\n### Setup";
+        let anchors = anchor_map(&build_tree(input));
+
+        assert_eq!(
+            anchors,
+            HashMap::from([
+                ("setup".to_string(), 1),
+                ("setup-1".to_string(), 3),
+                ("usage".to_string(), 4),
+                ("setup-2".to_string(), 5),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_render_table_of_contents() {
+        let input = "[TOC]\n# Intro\n## Setup\n### Linux\n## Usage";
+        let nodes = Parser::new().table_of_contents(true).parse(input);
+        let options = RenderOptions {
+            header_anchors: true,
+            ..RenderOptions::default()
+        };
+        let html = render_html_with_options(&nodes[..1], &options);
+
+        assert_eq!(
+            html,
+            "<nav class=\"table-of-contents\"><ul><li><a href=\"#intro\">Intro</a>\
+             <ul><li><a href=\"#setup\">Setup</a><ul><li><a href=\"#linux\">Linux</a></li></ul></li>\
+             <li><a href=\"#usage\">Usage</a></li></ul></li></ul></nav>"
+        );
+    }
+
+    #[test]
+    fn test_render_header_anchors() {
+        let input = "# Intro\n## Intro\n## Other *part*";
+        let options = RenderOptions {
+            header_anchors: true,
+            ..RenderOptions::default()
+        };
+        let html = render_html_with_options(&build_tree(input), &options);
+
+        assert_eq!(
+            html,
+            "<h1 id=\"intro\"><a href=\"#intro\">Intro</a></h1>\
+             <h2 id=\"intro-1\"><a href=\"#intro-1\">Intro</a></h2>\
+             <h2 id=\"other-part\"><a href=\"#other-part\">Other <em>part</em></a></h2>"
+        );
+    }
+
+    #[test]
+    fn test_render_without_paragraph_wrapping() {
+        let input = "some *text*\n\nnext";
+        let nodes = build_tree(input);
+        let options = RenderOptions {
+            wrap_paragraphs: false,
+            ..RenderOptions::default()
+        };
+
+        assert_eq!(
+            render_html(&nodes),
+            "<p>some <em>text</em></p>\n<p>next</p>"
+        );
+        assert_eq!(
+            render_html_with_options(&nodes, &options),
+            "some <em>text</em>\nnext"
+        );
+    }
+}
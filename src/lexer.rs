@@ -1,13 +1,41 @@
-use crate::token::{Token, TokenType};
+use std::collections::VecDeque;
+
+use crate::token::{BorrowedToken, Token, TokenType};
+
+/// The number of columns between tab stops, unless another width is given.
+pub const DEFAULT_TAB_WIDTH: usize = 4;
 
 struct CharStream<'a> {
     input: &'a str,
     position: usize,
+    column: usize,          // column of the character at `column_position`
+    column_position: usize, // byte position the column was last computed for
+    tab_width: usize,
 }
 
 impl<'a> CharStream<'a> {
-    pub fn new(input: &'a str) -> Self {
-        Self { input, position: 0 }
+    pub fn new(input: &'a str, tab_width: usize) -> Self {
+        Self {
+            input,
+            position: 0,
+            column: 1,
+            column_position: 0,
+            tab_width,
+        }
+    }
+
+    // Returns the 1-based column of the character at the byte `position`.
+    // Each character counts as one column, whatever its length in UTF-8,
+    // except a tab, which advances to the next tab stop.
+    // The positions must be given in increasing order.
+    pub fn column_at(&mut self, position: usize) -> usize {
+        let skipped = &self.input[self.column_position..position];
+        match skipped.rfind('\n') {
+            Some(ix) => self.column = column_after(1, &skipped[ix + 1..], self.tab_width),
+            None => self.column = column_after(self.column, skipped, self.tab_width),
+        }
+        self.column_position = position;
+        self.column
     }
 
     // Reads the next character without advancing the position.
@@ -41,176 +69,602 @@ impl<'a> CharStream<'a> {
         }
     }
 
-    // Consumes and returns a string until a separator (whitespace or newline) is found.
-    pub fn consume_until_separator(&mut self) -> String {
-        let mut result = String::new();
+    // Checks whether the input continues with `pattern` from the current position.
+    pub fn starts_with(&self, pattern: &str) -> bool {
+        self.input[self.position..].starts_with(pattern)
+    }
 
-        // Retrive the previous character (before calling this function) to ensure proper handling.
-        if let Some(c) = self.prev(1) {
-            if c.is_whitespace() || c == '\n' {
-                return result;
-            }
-            result.push(c);
+    // Reads the rest of the current line without advancing the position.
+    pub fn peek_line(&self) -> &'a str {
+        let rest = &self.input[self.position..];
+        match rest.find('\n') {
+            Some(end) => &rest[..end],
+            None => rest,
         }
+    }
+
+    // Consumes and returns the rest of the current line, leaving the newline unconsumed.
+    pub fn consume_line(&mut self) -> &'a str {
+        let line = self.peek_line();
+        self.position += line.len();
+        line
+    }
+
+    // Returns the input from the byte `start` up to the current position.
+    pub fn slice_from(&self, start: usize) -> &'a str {
+        &self.input[start..self.position]
+    }
+
+    // Checks whether a word character follows the run of underscores starting at the byte `position`.
+    pub fn is_word_after_underscores(&self, position: usize) -> bool {
+        self.input[position..]
+            .trim_start_matches('_')
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphanumeric())
+    }
 
+    // Consumes text until a separator (whitespace, newline or a markdown character) is found,
+    // and returns it from the byte `start`. The caller has already consumed the character
+    // at `start`, which is always part of the text.
+    pub fn consume_until_separator(&mut self, start: usize) -> &'a str {
         while let Some(c) = self.next() {
-            if c.is_whitespace() || c == '\n' || c == '*' {
+            // An underscore inside a word is part of the text, like in `foo_bar`.
+            let is_closing_underscore =
+                c == '_' && !self.is_word_after_underscores(self.position - 1);
+            let is_escape = c == '\\' && self.peek_next().is_some_and(is_escapable);
+            if c.is_whitespace()
+                || c == '\n'
+                || c == '*'
+                || (c == '~' && self.peek_next() == Some('~'))
+                || c == '`'
+                || matches!(c, '[' | ']' | '(' | ')' | '|')
+                || is_closing_underscore
+                || is_escape
+                || is_unknown(c)
+            {
                 // Move the position back if a separator is found.
                 self.position -= c.len_utf8();
                 break;
             }
-            result.push(c);
         }
-        result
+        self.slice_from(start)
     }
 }
 
+/// Returns the column after `text` when it starts at `column` on a line.
+///
+/// A tab advances to the next column after a multiple of `tab_width`, so with 4 a tab
+/// at column 1 or 3 ends at column 5. With a width of 1, a tab counts as one column.
+pub(crate) fn column_after(column: usize, text: &str, tab_width: usize) -> usize {
+    let tab_width = tab_width.max(1);
+    text.chars().fold(column, |column, c| match c {
+        '\t' => (column - 1) / tab_width * tab_width + tab_width + 1,
+        _ => column + 1,
+    })
+}
+
+// Checks whether a backslash before the character makes it a literal.
+pub(crate) fn is_escapable(c: char) -> bool {
+    matches!(
+        c,
+        '*' | '_' | '#' | '`' | '[' | ']' | '(' | ')' | '>' | '-' | '!' | '~' | '|' | '.'
+    )
+}
+
+// Checks whether the character is not valid in Markdown text, like a control character.
+// Tabs are whitespace and carriage returns are dropped, so neither is unknown.
+fn is_unknown(c: char) -> bool {
+    c.is_control() && !matches!(c, '\n' | '\t' | '\r')
+}
+
+/// Splits the input into tokens, in document order.
+///
+/// Each token records its line, column and byte range in the input.
+/// A tab advances the column to the next tab stop, every `DEFAULT_TAB_WIDTH` columns.
+/// A carriage return is never part of a token: `\r\n` lexes like `\n`, and a
+/// stray `\r` inside a line is dropped, splitting the text around it.
 pub fn lex(input: &str) -> Vec<Token> {
-    let mut stream = CharStream::new(input);
-    let mut tokens: Vec<Token> = Vec::new();
-    let mut line = 1;
+    lex_with_tab_width(input, DEFAULT_TAB_WIDTH)
+}
+
+/// Splits the input into tokens like `lex`, with tab stops every `tab_width` columns.
+///
+/// A width of 1 counts each tab as a single column.
+pub fn lex_with_tab_width(input: &str, tab_width: usize) -> Vec<Token> {
+    lex_tokens(input, tab_width).map(Token::from).collect()
+}
+
+/// Splits the input into tokens lazily, without copying their text.
+///
+/// Yields the same tokens as `lex`, with each value borrowed from `input`.
+pub fn lex_borrowed(input: &str) -> impl Iterator<Item = BorrowedToken<'_>> {
+    lex_tokens(input, DEFAULT_TAB_WIDTH)
+}
+
+fn lex_tokens(input: &str, tab_width: usize) -> Lexer<'_> {
+    Lexer {
+        stream: CharStream::new(input, tab_width),
+        line: 1,
+        in_code_block: false,
+        alert_depth: 0,
+        at_line_start: true,
+        after_indent: true,
+        pending: VecDeque::new(),
+    }
+}
+
+/// The iterator returned by `lex_borrowed`.
+struct Lexer<'a> {
+    stream: CharStream<'a>,
+    line: usize,
+    in_code_block: bool,
+    alert_depth: usize,
+    at_line_start: bool, // whether the next token starts a new line
+    after_indent: bool,  // whether only indentation precedes the next token on its line
+    pending: VecDeque<BorrowedToken<'a>>, // tokens lexed but not yielded yet
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = BorrowedToken<'a>;
 
-    // Process the input one character at a time.
-    while let Some(c) = stream.next() {
+    fn next(&mut self) -> Option<BorrowedToken<'a>> {
+        // Process the input one character at a time, until it produces a token.
+        while self.pending.is_empty() {
+            let c = self.stream.next()?;
+            self.lex_char(c);
+        }
+        self.pending.pop_front()
+    }
+}
+
+impl<'a> Lexer<'a> {
+    fn push(&mut self, token: BorrowedToken<'a>) {
+        self.at_line_start = token.token_type == TokenType::Eol;
+        self.after_indent = match token.token_type {
+            TokenType::Whitespace => self.after_indent,
+            TokenType::Eol => true,
+            _ => false,
+        };
+        self.pending.push_back(token);
+    }
+
+    // Lexes the character `c`, which has just been consumed.
+    fn lex_char(&mut self, c: char) {
+        let start_byte = self.stream.position - c.len_utf8();
+        let column = self.stream.column_at(start_byte);
         match c {
             '\n' => {
-                tokens.push(Token {
+                self.push(BorrowedToken {
                     token_type: TokenType::Eol,
-                    value: c.to_string(),
-                    line,
+                    value: self.stream.slice_from(start_byte),
+                    line: self.line,
+                    column,
+                    start_byte,
+                    end_byte: self.stream.position,
                 });
-                line += 1; // Increment the line count on a newline.
+                self.line += 1; // Increment the line count on a newline.
+
+                // The content of a code block is kept as one raw text token per line
+                while self.in_code_block {
+                    let start_byte = self.stream.position;
+                    let column = self.stream.column_at(start_byte);
+                    let raw = self.stream.consume_line();
+                    if raw.trim_end() == "```" {
+                        self.push(BorrowedToken {
+                            token_type: TokenType::CodeBlock,
+                            value: "```",
+                            line: self.line,
+                            column,
+                            start_byte,
+                            end_byte: start_byte + "```".len(),
+                        });
+                        self.in_code_block = false;
+                        break;
+                    }
+                    // A carriage return is dropped here too, splitting the line around it
+                    let mut piece_start = start_byte;
+                    for piece in raw.split('\r') {
+                        if !piece.is_empty() {
+                            let column = self.stream.column_at(piece_start);
+                            self.push(BorrowedToken {
+                                token_type: TokenType::Text,
+                                value: piece,
+                                line: self.line,
+                                column,
+                                start_byte: piece_start,
+                                end_byte: piece_start + piece.len(),
+                            });
+                        }
+                        piece_start += piece.len() + '\r'.len_utf8();
+                    }
+                    // Stop at the end of input even if the code block is not closed.
+                    let start_byte = self.stream.position;
+                    let column = self.stream.column_at(start_byte);
+                    if self.stream.next().is_none() {
+                        break;
+                    }
+                    self.push(BorrowedToken {
+                        token_type: TokenType::Eol,
+                        value: self.stream.slice_from(start_byte),
+                        line: self.line,
+                        column,
+                        start_byte,
+                        end_byte: self.stream.position,
+                    });
+                    self.line += 1;
+                }
             }
-            ' ' => tokens.push(Token {
-                token_type: TokenType::Whitespace,
-                value: c.to_string(),
-                line,
-            }),
-            '#' => tokens.push(Token {
+            ' ' | '\t' => {
+                // A run of spaces and tabs is one token, whose value keeps every character
+                while let Some(' ' | '\t') = self.stream.peek_next() {
+                    self.stream.next();
+                }
+                self.push(BorrowedToken {
+                    token_type: TokenType::Whitespace,
+                    value: self.stream.slice_from(start_byte),
+                    line: self.line,
+                    column,
+                    start_byte,
+                    end_byte: self.stream.position,
+                })
+            }
+            '#' => self.push(BorrowedToken {
                 token_type: TokenType::Header,
-                value: c.to_string(),
-                line,
+                value: self.stream.slice_from(start_byte),
+                line: self.line,
+                column,
+                start_byte,
+                end_byte: self.stream.position,
             }),
             '-' => {
-                if let Some(next) = stream.peek_next() {
-                    if next.is_whitespace() {
-                        tokens.push(Token {
-                            token_type: TokenType::UnorderedList,
-                            value: "- ".to_string(),
-                            line,
-                        });
-                        stream.next();
-                    } else {
-                        let text = stream.consume_until_separator();
-                        if text.is_empty() {
-                            continue;
-                        }
-
-                        tokens.push(Token {
-                            token_type: TokenType::Text,
-                            value: text,
-                            line,
-                        });
-                    }
+                if self
+                    .stream
+                    .peek_next()
+                    .is_some_and(|next| next.is_whitespace())
+                {
+                    self.stream.next();
+                    self.push(BorrowedToken {
+                        token_type: TokenType::UnorderedList,
+                        value: "- ",
+                        line: self.line,
+                        column,
+                        start_byte,
+                        end_byte: self.stream.position,
+                    });
+                } else {
+                    let text = self.stream.consume_until_separator(start_byte);
+                    self.push(BorrowedToken {
+                        token_type: TokenType::Text,
+                        value: text,
+                        line: self.line,
+                        column,
+                        start_byte,
+                        end_byte: self.stream.position,
+                    });
                 }
             }
-            '>' => tokens.push(Token {
+            '>' => self.push(BorrowedToken {
                 token_type: TokenType::BlockQuote,
-                value: c.to_string(),
-                line,
-            }),
-            '`' => tokens.push(Token {
-                token_type: TokenType::InlineCode,
-                value: c.to_string(),
-                line,
+                value: self.stream.slice_from(start_byte),
+                line: self.line,
+                column,
+                start_byte,
+                end_byte: self.stream.position,
             }),
+            '`' => {
+                if self.stream.starts_with("``") {
+                    // Only a fence at the start of a line opens a code block.
+                    if self.at_line_start {
+                        self.in_code_block = true;
+                    }
+                    self.stream.next();
+                    self.stream.next();
+                    self.push(BorrowedToken {
+                        token_type: TokenType::CodeBlock,
+                        value: "```",
+                        line: self.line,
+                        column,
+                        start_byte,
+                        end_byte: self.stream.position,
+                    });
+                } else {
+                    self.push(BorrowedToken {
+                        token_type: TokenType::InlineCode,
+                        value: self.stream.slice_from(start_byte),
+                        line: self.line,
+                        column,
+                        start_byte,
+                        end_byte: self.stream.position,
+                    })
+                }
+            }
             '*' => {
-                if let Some(prev) = stream.prev(2) {
-                    if prev == '*' {
-                        if let Some(last) = tokens.last_mut() {
-                            *last = Token {
-                                token_type: TokenType::Bold,
-                                value: "**".to_string(),
-                                line,
-                            };
-                            continue;
+                // Consume both stars at once when they form a bold marker.
+                if self.stream.peek_next() == Some('*') {
+                    self.stream.next();
+                    self.push(BorrowedToken {
+                        token_type: TokenType::Bold,
+                        value: "**",
+                        line: self.line,
+                        column,
+                        start_byte,
+                        end_byte: self.stream.position,
+                    })
+                } else {
+                    self.push(BorrowedToken {
+                        token_type: TokenType::Italic,
+                        value: self.stream.slice_from(start_byte),
+                        line: self.line,
+                        column,
+                        start_byte,
+                        end_byte: self.stream.position,
+                    })
+                }
+            }
+            '~' => {
+                // Only a doubled tilde marks strikethrough, a single one is plain text.
+                if self.stream.peek_next() == Some('~') {
+                    self.stream.next();
+                    self.push(BorrowedToken {
+                        token_type: TokenType::Strikethrough,
+                        value: "~~",
+                        line: self.line,
+                        column,
+                        start_byte,
+                        end_byte: self.stream.position,
+                    })
+                } else {
+                    let text = self.stream.consume_until_separator(start_byte);
+                    self.push(BorrowedToken {
+                        token_type: TokenType::Text,
+                        value: text,
+                        line: self.line,
+                        column,
+                        start_byte,
+                        end_byte: self.stream.position,
+                    });
+                }
+            }
+            '\\' => {
+                // A backslash before a markdown character emits the character as plain text.
+                match self.stream.peek_next() {
+                    Some(next) if is_escapable(next) => {
+                        self.stream.next();
+                        // An escaped backtick escapes its whole run, so an escaped fence stays literal
+                        if next == '`' {
+                            while self.stream.peek_next() == Some('`') {
+                                self.stream.next();
+                            }
                         }
-                    } else {
-                        tokens.push(Token {
-                            token_type: TokenType::Italic,
-                            value: c.to_string(),
-                            line,
-                        })
+                        self.push(BorrowedToken {
+                            token_type: TokenType::Text,
+                            value: self.stream.slice_from(start_byte + 1),
+                            line: self.line,
+                            column,
+                            start_byte,
+                            end_byte: self.stream.position,
+                        });
+                    }
+                    _ => {
+                        let text = self.stream.consume_until_separator(start_byte);
+                        self.push(BorrowedToken {
+                            token_type: TokenType::Text,
+                            value: text,
+                            line: self.line,
+                            column,
+                            start_byte,
+                            end_byte: self.stream.position,
+                        });
                     }
+                }
+            }
+            '_' => {
+                // Underscores only mark emphasis when they are not inside a word.
+                let is_intraword = self
+                    .stream
+                    .prev(2)
+                    .is_some_and(|prev| prev.is_alphanumeric())
+                    && self
+                        .stream
+                        .is_word_after_underscores(self.stream.position - 1);
+                if is_intraword {
+                    let text = self.stream.consume_until_separator(start_byte);
+                    self.push(BorrowedToken {
+                        token_type: TokenType::Text,
+                        value: text,
+                        line: self.line,
+                        column,
+                        start_byte,
+                        end_byte: self.stream.position,
+                    });
+                } else if self.stream.peek_next() == Some('_') {
+                    self.stream.next();
+                    self.push(BorrowedToken {
+                        token_type: TokenType::Bold,
+                        value: "__",
+                        line: self.line,
+                        column,
+                        start_byte,
+                        end_byte: self.stream.position,
+                    })
                 } else {
-                    tokens.push(Token {
+                    self.push(BorrowedToken {
                         token_type: TokenType::Italic,
-                        value: c.to_string(),
-                        line,
+                        value: self.stream.slice_from(start_byte),
+                        line: self.line,
+                        column,
+                        start_byte,
+                        end_byte: self.stream.position,
                     })
                 }
             }
-            '!' => tokens.push(Token {
+            '!' => self.push(BorrowedToken {
                 token_type: TokenType::Exclamation,
-                value: c.to_string(),
-                line,
+                value: self.stream.slice_from(start_byte),
+                line: self.line,
+                column,
+                start_byte,
+                end_byte: self.stream.position,
             }),
-            '{' => tokens.push(Token {
+            '|' => self.push(BorrowedToken {
+                token_type: TokenType::Pipe,
+                value: self.stream.slice_from(start_byte),
+                line: self.line,
+                column,
+                start_byte,
+                end_byte: self.stream.position,
+            }),
+            '{' => self.push(BorrowedToken {
                 token_type: TokenType::CarlyBracketOpen,
-                value: c.to_string(),
-                line,
+                value: self.stream.slice_from(start_byte),
+                line: self.line,
+                column,
+                start_byte,
+                end_byte: self.stream.position,
             }),
-            '}' => tokens.push(Token {
+            '}' => self.push(BorrowedToken {
                 token_type: TokenType::CarlyBracketClose,
-                value: c.to_string(),
-                line,
+                value: self.stream.slice_from(start_byte),
+                line: self.line,
+                column,
+                start_byte,
+                end_byte: self.stream.position,
+            }),
+            // A `^` starting a token may mark a footnote, as in `[^1]`
+            '^' => self.push(BorrowedToken {
+                token_type: TokenType::Annotation,
+                value: self.stream.slice_from(start_byte),
+                line: self.line,
+                column,
+                start_byte,
+                end_byte: self.stream.position,
             }),
-            '[' => tokens.push(Token {
+            '[' => self.push(BorrowedToken {
                 token_type: TokenType::SquareBracketOpen,
-                value: c.to_string(),
-                line,
+                value: self.stream.slice_from(start_byte),
+                line: self.line,
+                column,
+                start_byte,
+                end_byte: self.stream.position,
             }),
-            ']' => tokens.push(Token {
+            ']' => self.push(BorrowedToken {
                 token_type: TokenType::SquareBracketClose,
-                value: c.to_string(),
-                line,
+                value: self.stream.slice_from(start_byte),
+                line: self.line,
+                column,
+                start_byte,
+                end_byte: self.stream.position,
             }),
-            '(' => tokens.push(Token {
+            '(' => self.push(BorrowedToken {
                 token_type: TokenType::ParenthesisOpen,
-                value: c.to_string(),
-                line,
+                value: self.stream.slice_from(start_byte),
+                line: self.line,
+                column,
+                start_byte,
+                end_byte: self.stream.position,
             }),
-            ')' => tokens.push(Token {
+            ')' => self.push(BorrowedToken {
                 token_type: TokenType::ParenthesisClose,
-                value: c.to_string(),
-                line,
+                value: self.stream.slice_from(start_byte),
+                line: self.line,
+                column,
+                start_byte,
+                end_byte: self.stream.position,
             }),
-            ';' => tokens.push(Token {
+            ';' => self.push(BorrowedToken {
                 token_type: TokenType::SemiColon,
-                value: c.to_string(),
-                line,
+                value: self.stream.slice_from(start_byte),
+                line: self.line,
+                column,
+                start_byte,
+                end_byte: self.stream.position,
             }),
-            ':' => tokens.push(Token {
-                token_type: TokenType::Colon,
-                value: c.to_string(),
-                line,
+            ':' => {
+                // A line made of `:::` and an optional type word opens or closes an alert block.
+                if self.stream.starts_with("::") && self.at_line_start {
+                    let rest = self.stream.peek_line();
+                    let alert_type = rest[2..].trim();
+                    if alert_type.chars().all(|c| c.is_alphanumeric()) {
+                        let end_byte = self.stream.position + rest.trim_end().len();
+                        self.stream.consume_line();
+                        if alert_type.is_empty() && self.alert_depth > 0 {
+                            self.push(BorrowedToken {
+                                token_type: TokenType::AlertEnd,
+                                value: ":::",
+                                line: self.line,
+                                column,
+                                start_byte,
+                                end_byte,
+                            });
+                            self.alert_depth -= 1;
+                        } else {
+                            self.push(BorrowedToken {
+                                token_type: TokenType::AlertStart,
+                                value: alert_type,
+                                line: self.line,
+                                column,
+                                start_byte,
+                                end_byte,
+                            });
+                            self.alert_depth += 1;
+                        }
+                        return;
+                    }
+                }
+                self.push(BorrowedToken {
+                    token_type: TokenType::Colon,
+                    value: self.stream.slice_from(start_byte),
+                    line: self.line,
+                    column,
+                    start_byte,
+                    end_byte: self.stream.position,
+                })
+            }
+            // Other whitespace, like the `\r` of a CRLF line break, is skipped
+            c if c.is_whitespace() => {}
+            c if is_unknown(c) => self.push(BorrowedToken {
+                token_type: TokenType::Unknown,
+                value: self.stream.slice_from(start_byte),
+                line: self.line,
+                column,
+                start_byte,
+                end_byte: self.stream.position,
             }),
             _ => {
-                let text = stream.consume_until_separator();
-                if text.is_empty() {
-                    continue;
+                // Digits followed by `. ` at the start of a line are an ordered list marker.
+                if c.is_ascii_digit() && self.after_indent {
+                    let rest = self.stream.peek_line();
+                    let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+                    if rest[digits..].starts_with(". ") {
+                        let value = &self.stream.input[start_byte..self.stream.position + digits];
+                        for _ in 0..digits + 2 {
+                            self.stream.next();
+                        }
+                        self.push(BorrowedToken {
+                            token_type: TokenType::OrderedList,
+                            value,
+                            line: self.line,
+                            column,
+                            start_byte,
+                            end_byte: self.stream.position,
+                        });
+                        return;
+                    }
                 }
 
-                tokens.push(Token {
+                let text = self.stream.consume_until_separator(start_byte);
+                self.push(BorrowedToken {
                     token_type: TokenType::Text,
                     value: text,
-                    line,
+                    line: self.line,
+                    column,
+                    start_byte,
+                    end_byte: self.stream.position,
                 });
             }
         }
     }
-
-    tokens
 }
 
 #[cfg(test)]
@@ -229,11 +683,153 @@ mod tests {
             vec![Token {
                 token_type: TokenType::Header,
                 value: '#'.to_string(),
-                line: 1
+                line: 1,
+                column: 1,
+                start_byte: 0,
+                end_byte: 1,
             }]
         )
     }
 
+    #[test]
+    fn test_tab_is_whitespace() {
+        let input = "\ta";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens[0],
+            Token {
+                token_type: TokenType::Whitespace,
+                value: '\t'.to_string(),
+                line: 1,
+                column: 1,
+                start_byte: 0,
+                end_byte: 1,
+            }
+        )
+    }
+
+    #[test]
+    fn test_tab_advances_to_tab_stop() {
+        // A leading tab ends at the first tab stop
+        let tokens = lex("\ta");
+        assert_eq!(tokens[1].column, 5);
+
+        // A tab in the middle of a line ends at the next tab stop, not 4 columns later
+        let tokens = lex("ab\tc");
+        assert_eq!(tokens[1].column, 3);
+        assert_eq!(tokens[2].column, 5);
+
+        let tokens = lex_with_tab_width("ab\tc", 1);
+        assert_eq!(tokens[2].column, 4);
+    }
+
+    #[test]
+    fn test_whitespace_run() {
+        let input = "a    b";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::Text,
+                    value: "a".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 1,
+                },
+                Token {
+                    token_type: TokenType::Whitespace,
+                    value: "    ".to_string(),
+                    line: 1,
+                    column: 2,
+                    start_byte: 1,
+                    end_byte: 5,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "b".to_string(),
+                    line: 1,
+                    column: 6,
+                    start_byte: 5,
+                    end_byte: 6,
+                },
+            ]
+        )
+    }
+
+    #[test]
+    fn test_borrowed_tokens_match_owned_tokens() {
+        let input = "# Title\n- *a* \\* 12. b\n1. `c`\n:::Warning\n```rust\nlet x;\n```\n:::";
+        let borrowed: Vec<Token> = lex_borrowed(input).map(Token::from).collect();
+
+        assert_eq!(borrowed, lex(input));
+        assert!(lex_borrowed(input)
+            .all(|token| input[token.start_byte..token.end_byte].contains(token.value)));
+    }
+
+    #[test]
+    fn test_header_marker_columns() {
+        let input = "text\n## 見出し text";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens[2..],
+            vec![
+                Token {
+                    token_type: TokenType::Header,
+                    value: '#'.to_string(),
+                    line: 2,
+                    column: 1,
+                    start_byte: 5,
+                    end_byte: 6,
+                },
+                Token {
+                    token_type: TokenType::Header,
+                    value: '#'.to_string(),
+                    line: 2,
+                    column: 2,
+                    start_byte: 6,
+                    end_byte: 7,
+                },
+                Token {
+                    token_type: TokenType::Whitespace,
+                    value: ' '.to_string(),
+                    line: 2,
+                    column: 3,
+                    start_byte: 7,
+                    end_byte: 8,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "見出し".to_string(),
+                    line: 2,
+                    column: 4,
+                    start_byte: 8,
+                    end_byte: 17,
+                },
+                Token {
+                    token_type: TokenType::Whitespace,
+                    value: ' '.to_string(),
+                    line: 2,
+                    column: 7,
+                    start_byte: 17,
+                    end_byte: 18,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "text".to_string(),
+                    line: 2,
+                    column: 8,
+                    start_byte: 18,
+                    end_byte: 22,
+                },
+            ]
+        )
+    }
+
     #[test]
     fn test_multiple_markers() {
         let input = "# > ` * !";
@@ -246,46 +842,73 @@ mod tests {
                     token_type: TokenType::Header,
                     value: '#'.to_string(),
                     line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 1,
                 },
                 Token {
                     token_type: TokenType::Whitespace,
                     value: ' '.to_string(),
                     line: 1,
+                    column: 2,
+                    start_byte: 1,
+                    end_byte: 2,
                 },
                 Token {
                     token_type: TokenType::BlockQuote,
                     value: '>'.to_string(),
                     line: 1,
+                    column: 3,
+                    start_byte: 2,
+                    end_byte: 3,
                 },
                 Token {
                     token_type: TokenType::Whitespace,
                     value: ' '.to_string(),
                     line: 1,
+                    column: 4,
+                    start_byte: 3,
+                    end_byte: 4,
                 },
                 Token {
                     token_type: TokenType::InlineCode,
                     value: '`'.to_string(),
                     line: 1,
+                    column: 5,
+                    start_byte: 4,
+                    end_byte: 5,
                 },
                 Token {
                     token_type: TokenType::Whitespace,
                     value: ' '.to_string(),
                     line: 1,
+                    column: 6,
+                    start_byte: 5,
+                    end_byte: 6,
                 },
                 Token {
                     token_type: TokenType::Italic,
                     value: '*'.to_string(),
                     line: 1,
+                    column: 7,
+                    start_byte: 6,
+                    end_byte: 7,
                 },
                 Token {
                     token_type: TokenType::Whitespace,
                     value: ' '.to_string(),
                     line: 1,
+                    column: 8,
+                    start_byte: 7,
+                    end_byte: 8,
                 },
                 Token {
                     token_type: TokenType::Exclamation,
                     value: '!'.to_string(),
                     line: 1,
+                    column: 9,
+                    start_byte: 8,
+                    end_byte: 9,
                 }
             ]
         );
@@ -303,31 +926,49 @@ mod tests {
                     token_type: TokenType::UnorderedList,
                     value: "- ".to_string(),
                     line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 2,
                 },
                 Token {
                     token_type: TokenType::Text,
                     value: "list".to_string(),
                     line: 1,
+                    column: 3,
+                    start_byte: 2,
+                    end_byte: 6,
                 },
                 Token {
                     token_type: TokenType::Eol,
                     value: "\n".to_string(),
                     line: 1,
+                    column: 7,
+                    start_byte: 6,
+                    end_byte: 7,
                 },
                 Token {
                     token_type: TokenType::Whitespace,
                     value: " ".to_string(),
-                    line: 2
+                    line: 2,
+                    column: 1,
+                    start_byte: 7,
+                    end_byte: 8,
                 },
                 Token {
                     token_type: TokenType::UnorderedList,
                     value: "- ".to_string(),
                     line: 2,
+                    column: 2,
+                    start_byte: 8,
+                    end_byte: 10,
                 },
                 Token {
                     token_type: TokenType::Text,
                     value: "list1-1".to_string(),
                     line: 2,
+                    column: 4,
+                    start_byte: 10,
+                    end_byte: 17,
                 },
             ]
         );
@@ -344,6 +985,9 @@ mod tests {
                 token_type: TokenType::Text,
                 value: "-list".to_string(),
                 line: 1,
+                column: 1,
+                start_byte: 0,
+                end_byte: 5,
             },]
         );
     }
@@ -360,31 +1004,49 @@ mod tests {
                     token_type: TokenType::Text,
                     value: "Hello,".to_string(),
                     line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 6,
                 },
                 Token {
                     token_type: TokenType::Whitespace,
                     value: ' '.to_string(),
                     line: 1,
+                    column: 7,
+                    start_byte: 6,
+                    end_byte: 7,
                 },
                 Token {
                     token_type: TokenType::Text,
                     value: "world!".to_string(),
                     line: 1,
+                    column: 8,
+                    start_byte: 7,
+                    end_byte: 13,
                 },
                 Token {
                     token_type: TokenType::Whitespace,
                     value: ' '.to_string(),
                     line: 1,
+                    column: 14,
+                    start_byte: 13,
+                    end_byte: 14,
                 },
                 Token {
                     token_type: TokenType::Header,
                     value: '#'.to_string(),
                     line: 1,
+                    column: 15,
+                    start_byte: 14,
+                    end_byte: 15,
                 },
                 Token {
                     token_type: TokenType::Text,
                     value: "Markdown".to_string(),
                     line: 1,
+                    column: 16,
+                    start_byte: 15,
+                    end_byte: 23,
                 },
             ]
         );
@@ -402,43 +1064,888 @@ mod tests {
                     token_type: TokenType::Italic,
                     value: "*".to_string(),
                     line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 1,
                 },
                 Token {
                     token_type: TokenType::Text,
                     value: "italic".to_string(),
                     line: 1,
+                    column: 2,
+                    start_byte: 1,
+                    end_byte: 7,
                 },
                 Token {
                     token_type: TokenType::Italic,
                     value: "*".to_string(),
                     line: 1,
+                    column: 8,
+                    start_byte: 7,
+                    end_byte: 8,
                 },
             ]
         );
     }
 
     #[test]
-    fn test_bold_markers() {
-        let input = "**bold**";
+    fn test_italic_markers_inside_word() {
+        let input = "a*b*c";
         let tokens = lex(input);
 
         assert_eq!(
             tokens,
             vec![
                 Token {
-                    token_type: TokenType::Bold,
-                    value: "**".to_string(),
+                    token_type: TokenType::Text,
+                    value: "a".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 1,
+                },
+                Token {
+                    token_type: TokenType::Italic,
+                    value: "*".to_string(),
                     line: 1,
+                    column: 2,
+                    start_byte: 1,
+                    end_byte: 2,
                 },
                 Token {
                     token_type: TokenType::Text,
-                    value: "bold".to_string(),
+                    value: "b".to_string(),
                     line: 1,
+                    column: 3,
+                    start_byte: 2,
+                    end_byte: 3,
                 },
                 Token {
-                    token_type: TokenType::Bold,
-                    value: "**".to_string(),
+                    token_type: TokenType::Italic,
+                    value: "*".to_string(),
+                    line: 1,
+                    column: 4,
+                    start_byte: 3,
+                    end_byte: 4,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "c".to_string(),
+                    line: 1,
+                    column: 5,
+                    start_byte: 4,
+                    end_byte: 5,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_italic_marker_after_word() {
+        let input = "word*";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::Text,
+                    value: "word".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 4,
+                },
+                Token {
+                    token_type: TokenType::Italic,
+                    value: "*".to_string(),
+                    line: 1,
+                    column: 5,
+                    start_byte: 4,
+                    end_byte: 5,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_italic_marker_before_word() {
+        let input = "*word";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::Italic,
+                    value: "*".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 1,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "word".to_string(),
+                    line: 1,
+                    column: 2,
+                    start_byte: 1,
+                    end_byte: 5,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trailing_dash() {
+        let input = "a -";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::Text,
+                    value: "a".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 1,
+                },
+                Token {
+                    token_type: TokenType::Whitespace,
+                    value: " ".to_string(),
+                    line: 1,
+                    column: 2,
+                    start_byte: 1,
+                    end_byte: 2,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "-".to_string(),
                     line: 1,
+                    column: 3,
+                    start_byte: 2,
+                    end_byte: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_carriage_return_is_dropped() {
+        let input = "a\rb\n```\r\nx\ry\r\n```";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::Text,
+                    value: "a".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 1,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "b".to_string(),
+                    line: 1,
+                    column: 3,
+                    start_byte: 2,
+                    end_byte: 3,
+                },
+                Token {
+                    token_type: TokenType::Eol,
+                    value: "\n".to_string(),
+                    line: 1,
+                    column: 4,
+                    start_byte: 3,
+                    end_byte: 4,
+                },
+                Token {
+                    token_type: TokenType::CodeBlock,
+                    value: "```".to_string(),
+                    line: 2,
+                    column: 1,
+                    start_byte: 4,
+                    end_byte: 7,
+                },
+                Token {
+                    token_type: TokenType::Eol,
+                    value: "\n".to_string(),
+                    line: 2,
+                    column: 5,
+                    start_byte: 8,
+                    end_byte: 9,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "x".to_string(),
+                    line: 3,
+                    column: 1,
+                    start_byte: 9,
+                    end_byte: 10,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "y".to_string(),
+                    line: 3,
+                    column: 3,
+                    start_byte: 11,
+                    end_byte: 12,
+                },
+                Token {
+                    token_type: TokenType::Eol,
+                    value: "\n".to_string(),
+                    line: 3,
+                    column: 5,
+                    start_byte: 13,
+                    end_byte: 14,
+                },
+                Token {
+                    token_type: TokenType::CodeBlock,
+                    value: "```".to_string(),
+                    line: 4,
+                    column: 1,
+                    start_byte: 14,
+                    end_byte: 17,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pipe() {
+        let input = "a|b";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::Text,
+                    value: "a".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 1,
+                },
+                Token {
+                    token_type: TokenType::Pipe,
+                    value: "|".to_string(),
+                    line: 1,
+                    column: 2,
+                    start_byte: 1,
+                    end_byte: 2,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "b".to_string(),
+                    line: 1,
+                    column: 3,
+                    start_byte: 2,
+                    end_byte: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_annotation() {
+        let input = "[^1] ^";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::SquareBracketOpen,
+                    value: "[".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 1,
+                },
+                Token {
+                    token_type: TokenType::Annotation,
+                    value: "^".to_string(),
+                    line: 1,
+                    column: 2,
+                    start_byte: 1,
+                    end_byte: 2,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "1".to_string(),
+                    line: 1,
+                    column: 3,
+                    start_byte: 2,
+                    end_byte: 3,
+                },
+                Token {
+                    token_type: TokenType::SquareBracketClose,
+                    value: "]".to_string(),
+                    line: 1,
+                    column: 4,
+                    start_byte: 3,
+                    end_byte: 4,
+                },
+                Token {
+                    token_type: TokenType::Whitespace,
+                    value: " ".to_string(),
+                    line: 1,
+                    column: 5,
+                    start_byte: 4,
+                    end_byte: 5,
+                },
+                Token {
+                    token_type: TokenType::Annotation,
+                    value: "^".to_string(),
+                    line: 1,
+                    column: 6,
+                    start_byte: 5,
+                    end_byte: 6,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bold_markers() {
+        let input = "**bold**";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::Bold,
+                    value: "**".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 2,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "bold".to_string(),
+                    line: 1,
+                    column: 3,
+                    start_byte: 2,
+                    end_byte: 6,
+                },
+                Token {
+                    token_type: TokenType::Bold,
+                    value: "**".to_string(),
+                    line: 1,
+                    column: 7,
+                    start_byte: 6,
+                    end_byte: 8,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bold_markers_in_any_position() {
+        for (input, line, column, byte) in [
+            ("**x**", 1, 1, 0),
+            ("a\n**x**", 2, 1, 2),
+            ("a **x**", 1, 3, 2),
+        ] {
+            let tokens = lex(input);
+
+            assert_eq!(
+                tokens[tokens.len() - 3..],
+                vec![
+                    Token {
+                        token_type: TokenType::Bold,
+                        value: "**".to_string(),
+                        line,
+                        column,
+                        start_byte: byte,
+                        end_byte: byte + 2,
+                    },
+                    Token {
+                        token_type: TokenType::Text,
+                        value: "x".to_string(),
+                        line,
+                        column: column + 2,
+                        start_byte: byte + 2,
+                        end_byte: byte + 3,
+                    },
+                    Token {
+                        token_type: TokenType::Bold,
+                        value: "**".to_string(),
+                        line,
+                        column: column + 3,
+                        start_byte: byte + 3,
+                        end_byte: byte + 5,
+                    },
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_underscore_italic_markers() {
+        let input = "_italic_";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::Italic,
+                    value: "_".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 1,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "italic".to_string(),
+                    line: 1,
+                    column: 2,
+                    start_byte: 1,
+                    end_byte: 7,
+                },
+                Token {
+                    token_type: TokenType::Italic,
+                    value: "_".to_string(),
+                    line: 1,
+                    column: 8,
+                    start_byte: 7,
+                    end_byte: 8,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_underscore_bold_markers() {
+        let input = "__bold__";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::Bold,
+                    value: "__".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 2,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "bold".to_string(),
+                    line: 1,
+                    column: 3,
+                    start_byte: 2,
+                    end_byte: 6,
+                },
+                Token {
+                    token_type: TokenType::Bold,
+                    value: "__".to_string(),
+                    line: 1,
+                    column: 7,
+                    start_byte: 6,
+                    end_byte: 8,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_intraword_underscore() {
+        let input = "foo_bar";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![Token {
+                token_type: TokenType::Text,
+                value: "foo_bar".to_string(),
+                line: 1,
+                column: 1,
+                start_byte: 0,
+                end_byte: 7,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_escaped_italic_markers() {
+        let input = "\\*literal\\*";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::Text,
+                    value: "*".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 2,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "literal".to_string(),
+                    line: 1,
+                    column: 3,
+                    start_byte: 2,
+                    end_byte: 9,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "*".to_string(),
+                    line: 1,
+                    column: 10,
+                    start_byte: 9,
+                    end_byte: 11,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escaped_header_marker() {
+        let input = "\\#notheader";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::Text,
+                    value: "#".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 2,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "notheader".to_string(),
+                    line: 1,
+                    column: 3,
+                    start_byte: 2,
+                    end_byte: 11,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escaped_strikethrough_and_pipe() {
+        let input = "\\~~a\\|";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::Text,
+                    value: "~".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 2,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "~a".to_string(),
+                    line: 1,
+                    column: 3,
+                    start_byte: 2,
+                    end_byte: 4,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "|".to_string(),
+                    line: 1,
+                    column: 5,
+                    start_byte: 4,
+                    end_byte: 6,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trailing_backslash() {
+        let input = "end \\";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::Text,
+                    value: "end".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 3,
+                },
+                Token {
+                    token_type: TokenType::Whitespace,
+                    value: ' '.to_string(),
+                    line: 1,
+                    column: 4,
+                    start_byte: 3,
+                    end_byte: 4,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: '\\'.to_string(),
+                    line: 1,
+                    column: 5,
+                    start_byte: 4,
+                    end_byte: 5,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_control_character() {
+        let input = "a\u{1}b";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::Text,
+                    value: "a".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 1,
+                },
+                Token {
+                    token_type: TokenType::Unknown,
+                    value: "\u{1}".to_string(),
+                    line: 1,
+                    column: 2,
+                    start_byte: 1,
+                    end_byte: 2,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "b".to_string(),
+                    line: 1,
+                    column: 3,
+                    start_byte: 2,
+                    end_byte: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ordered_list() {
+        let input = "10. list\n 2.5";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::OrderedList,
+                    value: "10".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 4,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "list".to_string(),
+                    line: 1,
+                    column: 5,
+                    start_byte: 4,
+                    end_byte: 8,
+                },
+                Token {
+                    token_type: TokenType::Eol,
+                    value: "\n".to_string(),
+                    line: 1,
+                    column: 9,
+                    start_byte: 8,
+                    end_byte: 9,
+                },
+                Token {
+                    token_type: TokenType::Whitespace,
+                    value: " ".to_string(),
+                    line: 2,
+                    column: 1,
+                    start_byte: 9,
+                    end_byte: 10,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "2.5".to_string(),
+                    line: 2,
+                    column: 2,
+                    start_byte: 10,
+                    end_byte: 13,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inline_code_markers() {
+        let input = "`code`";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::InlineCode,
+                    value: "`".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 1,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "code".to_string(),
+                    line: 1,
+                    column: 2,
+                    start_byte: 1,
+                    end_byte: 5,
+                },
+                Token {
+                    token_type: TokenType::InlineCode,
+                    value: "`".to_string(),
+                    line: 1,
+                    column: 6,
+                    start_byte: 5,
+                    end_byte: 6,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_code_block_markers() {
+        let input = "```rust\n  - *raw*\n```";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::CodeBlock,
+                    value: "```".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 3,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "rust".to_string(),
+                    line: 1,
+                    column: 4,
+                    start_byte: 3,
+                    end_byte: 7,
+                },
+                Token {
+                    token_type: TokenType::Eol,
+                    value: "\n".to_string(),
+                    line: 1,
+                    column: 8,
+                    start_byte: 7,
+                    end_byte: 8,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "  - *raw*".to_string(),
+                    line: 2,
+                    column: 1,
+                    start_byte: 8,
+                    end_byte: 17,
+                },
+                Token {
+                    token_type: TokenType::Eol,
+                    value: "\n".to_string(),
+                    line: 2,
+                    column: 10,
+                    start_byte: 17,
+                    end_byte: 18,
+                },
+                Token {
+                    token_type: TokenType::CodeBlock,
+                    value: "```".to_string(),
+                    line: 3,
+                    column: 1,
+                    start_byte: 18,
+                    end_byte: 21,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alert_block_markers() {
+        let input = ":::warning\ntext\n:::";
+        let tokens = lex(input);
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    token_type: TokenType::AlertStart,
+                    value: "warning".to_string(),
+                    line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 10,
+                },
+                Token {
+                    token_type: TokenType::Eol,
+                    value: "\n".to_string(),
+                    line: 1,
+                    column: 11,
+                    start_byte: 10,
+                    end_byte: 11,
+                },
+                Token {
+                    token_type: TokenType::Text,
+                    value: "text".to_string(),
+                    line: 2,
+                    column: 1,
+                    start_byte: 11,
+                    end_byte: 15,
+                },
+                Token {
+                    token_type: TokenType::Eol,
+                    value: "\n".to_string(),
+                    line: 2,
+                    column: 5,
+                    start_byte: 15,
+                    end_byte: 16,
+                },
+                Token {
+                    token_type: TokenType::AlertEnd,
+                    value: ":::".to_string(),
+                    line: 3,
+                    column: 1,
+                    start_byte: 16,
+                    end_byte: 19,
                 },
             ]
         );
@@ -456,46 +1963,73 @@ mod tests {
                     token_type: TokenType::Header,
                     value: '#'.to_string(),
                     line: 1,
+                    column: 1,
+                    start_byte: 0,
+                    end_byte: 1,
                 },
                 Token {
                     token_type: TokenType::Whitespace,
                     value: ' '.to_string(),
                     line: 1,
+                    column: 2,
+                    start_byte: 1,
+                    end_byte: 2,
                 },
                 Token {
                     token_type: TokenType::Text,
                     value: "Header".to_string(),
                     line: 1,
+                    column: 3,
+                    start_byte: 2,
+                    end_byte: 8,
                 },
                 Token {
                     token_type: TokenType::Eol,
                     value: '\n'.to_string(),
                     line: 1,
+                    column: 9,
+                    start_byte: 8,
+                    end_byte: 9,
                 },
                 Token {
                     token_type: TokenType::UnorderedList,
                     value: "- ".to_string(),
                     line: 2,
+                    column: 1,
+                    start_byte: 9,
+                    end_byte: 11,
                 },
                 Token {
                     token_type: TokenType::Text,
                     value: "List".to_string(),
                     line: 2,
+                    column: 3,
+                    start_byte: 11,
+                    end_byte: 15,
                 },
                 Token {
                     token_type: TokenType::Whitespace,
                     value: ' '.to_string(),
                     line: 2,
+                    column: 7,
+                    start_byte: 15,
+                    end_byte: 16,
                 },
                 Token {
                     token_type: TokenType::Text,
                     value: "Item".to_string(),
                     line: 2,
+                    column: 8,
+                    start_byte: 16,
+                    end_byte: 20,
                 },
                 Token {
                     token_type: TokenType::Eol,
                     value: '\n'.to_string(),
                     line: 2,
+                    column: 12,
+                    start_byte: 20,
+                    end_byte: 21,
                 },
             ]
         );
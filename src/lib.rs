@@ -1,4 +1,90 @@
+//! A Markdown parser that turns a document into a tree of nodes with source positions.
+//!
+//! The raw token stream is available too, for tooling such as syntax highlighters:
+//!
+//! ```
+//! use twigmd::{lex, Token, TokenType};
+//!
+//! assert_eq!(
+//!     lex("# Hi"),
+//!     vec![
+//!         Token {
+//!             token_type: TokenType::Header,
+//!             value: "#".to_string(),
+//!             line: 1,
+//!             column: 1,
+//!             start_byte: 0,
+//!             end_byte: 1,
+//!         },
+//!         Token {
+//!             token_type: TokenType::Whitespace,
+//!             value: " ".to_string(),
+//!             line: 1,
+//!             column: 2,
+//!             start_byte: 1,
+//!             end_byte: 2,
+//!         },
+//!         Token {
+//!             token_type: TokenType::Text,
+//!             value: "Hi".to_string(),
+//!             line: 1,
+//!             column: 3,
+//!             start_byte: 2,
+//!             end_byte: 4,
+//!         },
+//!     ]
+//! );
+//! ```
+
+pub mod html;
 pub mod lexer;
+pub mod lint;
 pub mod parser;
-mod token;
+pub mod render_md;
+pub mod token;
 pub mod tree;
+
+pub use html::render_node_html;
+pub use lexer::{lex, lex_borrowed, lex_with_tab_width};
+pub use parser::build_tree;
+pub use token::{BorrowedToken, Token, TokenType};
+pub use tree::{
+    Alert, AlertType, Alignment, Bold, ByteSpan, CodeBlock, DocumentSummary, Eol, FootnoteDef,
+    FootnoteRef, FrontMatter, HardBreak, Header, HeadingEntry, Image, InlineCode, Italic, LineSpan,
+    Link, List, ListItem, Node, NodeId, NodeKind, OrderedList, Paragraph, Strikethrough, Table,
+    TableOfContents, Text, UnorderedList, Whitespace,
+};
+
+/// Parses a Markdown string and returns its tree serialized as JSON.
+///
+/// This keeps `Node` out of the signature, for callers across an FFI boundary such as
+/// wasm-bindgen. A serialization error is returned as its message.
+///
+/// The output is deterministic: the same input always gives byte-identical JSON,
+/// since nodes are kept in document order and reference definitions are looked up
+/// in sorted maps, so it can be compared against golden files.
+pub fn parse_to_json(input: &str) -> Result<String, String> {
+    serde_json::to_string(&build_tree(input)).map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_to_json() {
+        let json = parse_to_json("# Hi").unwrap();
+
+        assert!(json.starts_with("[{\"Header\":{\"level\":1,"));
+    }
+
+    #[test]
+    fn test_parse_to_json_is_deterministic() {
+        let input = "[a], [b][c] and [d][]\n\n[d]: /d\n[c]: /c \"C\"\n[b]: /b\n[a]: /a";
+        let json = parse_to_json(input).unwrap();
+
+        for _ in 0..20 {
+            assert_eq!(parse_to_json(input).unwrap(), json);
+        }
+    }
+}
@@ -0,0 +1,388 @@
+use crate::{
+    lexer::{column_after, lex, DEFAULT_TAB_WIDTH},
+    token::{Token, TokenType},
+    tree::{span_offset, walk_with_depth, ByteSpan, LineSpan, Node},
+};
+
+/// A problem found in a document, reported at the position it occurs.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LintIssue {
+    pub message: String,
+    pub position: LineSpan,
+}
+
+/// Block-level HTML elements, which must be closed with an end tag.
+const HTML_BLOCK_TAGS: &[&str] = &[
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "div",
+    "figure",
+    "footer",
+    "form",
+    "header",
+    "nav",
+    "ol",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+/// Checks that every multi-line construct in the document is closed.
+///
+/// Code fences (```` ``` ````), alert blocks (`:::`) and block-level HTML elements
+/// such as `<div>` are checked. Each unclosed construct is reported at the position
+/// of its opening fence or tag. HTML inside a code block or a code span is not checked.
+pub fn validate_fences(input: &str) -> Result<(), Vec<LintIssue>> {
+    let tokens = lex(input);
+    let mut open_code_block: Option<&Token> = None;
+    let mut open_alerts: Vec<&Token> = Vec::new();
+    let mut code_lines: Vec<(usize, usize)> = Vec::new();
+
+    for (ix, token) in tokens.iter().enumerate() {
+        match token.token_type {
+            // Only fences at the start of a line open or close a code block
+            TokenType::CodeBlock if ix == 0 || tokens[ix - 1].token_type == TokenType::Eol => {
+                open_code_block = match open_code_block {
+                    Some(open) => {
+                        code_lines.push((open.line, token.line));
+                        None
+                    }
+                    None => Some(token),
+                };
+            }
+            TokenType::AlertStart => open_alerts.push(token),
+            TokenType::AlertEnd => {
+                open_alerts.pop();
+            }
+            _ => {}
+        }
+    }
+
+    let mut issues: Vec<LintIssue> = open_alerts
+        .into_iter()
+        .map(|token| LintIssue {
+            message: "unclosed alert block".to_string(),
+            position: fence_span(token, format!(":::{}", token.value).chars().count()),
+        })
+        .collect();
+    if let Some(token) = open_code_block {
+        code_lines.push((token.line, usize::MAX));
+        issues.push(LintIssue {
+            message: "unclosed code block".to_string(),
+            position: fence_span(token, token.value.chars().count()),
+        });
+    }
+    issues.extend(unclosed_html_blocks(input, &code_lines));
+    issues.sort_by_key(|issue| (issue.position.start, issue.position.start_col));
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(issues)
+    }
+}
+
+/// Returns an issue for each block-level HTML element that has no end tag.
+///
+/// Start and end tags are paired by name, like `<div>` with `</div>`, anywhere in
+/// the document except on the lines in `code_lines` and inside code spans.
+fn unclosed_html_blocks(input: &str, code_lines: &[(usize, usize)]) -> Vec<LintIssue> {
+    let mut open_tags: Vec<(&str, LineSpan)> = Vec::new();
+    let mut line_start = 0;
+    for (line_ix, line) in input.split('\n').enumerate() {
+        let line_number = line_ix + 1;
+        let in_code_block = code_lines
+            .iter()
+            .any(|&(start, end)| (start..=end).contains(&line_number));
+        for (ix, _) in line.match_indices('<').filter(|_| !in_code_block) {
+            // An odd number of backticks before the tag puts it in a code span
+            if line[..ix].matches('`').count() % 2 == 1 {
+                continue;
+            }
+            let rest = &line[ix + 1..];
+            let (is_end, rest) = match rest.strip_prefix('/') {
+                Some(rest) => (true, rest),
+                None => (false, rest),
+            };
+            let name_len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .unwrap_or(rest.len());
+            let Some(tag) = HTML_BLOCK_TAGS
+                .iter()
+                .find(|tag| rest[..name_len].eq_ignore_ascii_case(tag))
+            else {
+                continue;
+            };
+            let tag_end = rest.find('>').map(|end| &rest[..end]);
+            if is_end {
+                if let Some(open) = open_tags.iter().rposition(|(open, _)| open == tag) {
+                    open_tags.remove(open);
+                }
+            } else if !tag_end.is_some_and(|tag| tag.ends_with('/')) {
+                let column = column_after(1, &line[..ix], DEFAULT_TAB_WIDTH);
+                let byte = line_start + ix;
+                open_tags.push((
+                    tag,
+                    LineSpan {
+                        start: span_offset(line_number),
+                        end: span_offset(line_number),
+                        start_col: span_offset(column),
+                        end_col: span_offset(column + 1 + name_len),
+                        bytes: ByteSpan {
+                            start: span_offset(byte),
+                            end: span_offset(byte + 1 + name_len),
+                        },
+                    },
+                ));
+            }
+        }
+        line_start += line.len() + 1;
+    }
+
+    open_tags
+        .into_iter()
+        .map(|(tag, position)| LintIssue {
+            message: format!("unclosed HTML block `<{}>`", tag),
+            position,
+        })
+        .collect()
+}
+
+/// Flags every image whose alt text is empty or only whitespace.
+///
+/// Each issue is reported at the position of the image, in document order.
+pub fn lint_image_alt(nodes: &[Node]) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    walk_with_depth(nodes, |_, node| {
+        if let Node::Image(image) = node {
+            if image.alt.trim().is_empty() {
+                issues.push(LintIssue {
+                    message: format!("image `{}` has no alt text", image.src),
+                    position: image.position.clone(),
+                });
+            }
+        }
+    });
+    issues
+}
+
+/// Finds the inline markers that have no matching marker on the same line.
+///
+/// Emphasis (`*`, `_`, `**`, `__`, `~~`), code spans and `[`/`]` brackets are checked,
+/// using only the tokens of the input so it is cheap enough to run on every keystroke.
+/// Markers inside a code span are text. Each marker is returned with its first
+/// character and its span, in document order.
+pub fn unbalanced_markers(input: &str) -> Vec<(char, LineSpan)> {
+    let tokens = lex(input);
+    let mut unbalanced: Vec<&Token> = Vec::new();
+    let mut open_code: Option<&Token> = None;
+    let mut open_emphasis: Vec<&Token> = Vec::new();
+    let mut open_brackets: Vec<&Token> = Vec::new();
+
+    for token in &tokens {
+        if let Some(code) = open_code {
+            // A code span only closes with a run of the same length
+            if token.token_type == TokenType::InlineCode && token.value == code.value {
+                open_code = None;
+                continue;
+            } else if token.token_type != TokenType::Eol {
+                continue;
+            }
+        }
+        match token.token_type {
+            TokenType::InlineCode => open_code = Some(token),
+            TokenType::Italic | TokenType::Bold | TokenType::Strikethrough => {
+                match open_emphasis
+                    .iter()
+                    .rposition(|open| open.value == token.value)
+                {
+                    Some(ix) => {
+                        open_emphasis.remove(ix);
+                    }
+                    None => open_emphasis.push(token),
+                }
+            }
+            TokenType::SquareBracketOpen => open_brackets.push(token),
+            TokenType::SquareBracketClose if open_brackets.pop().is_none() => {
+                unbalanced.push(token);
+            }
+            // Inline markers do not pair across lines
+            TokenType::Eol => {
+                unbalanced.extend(open_code.take());
+                unbalanced.append(&mut open_emphasis);
+                unbalanced.append(&mut open_brackets);
+            }
+            _ => {}
+        }
+    }
+    unbalanced.extend(open_code);
+    unbalanced.append(&mut open_emphasis);
+    unbalanced.append(&mut open_brackets);
+
+    unbalanced.sort_by_key(|token| token.start_byte);
+    unbalanced
+        .into_iter()
+        .filter_map(|token| {
+            let marker = token.value.chars().next()?;
+            Some((marker, fence_span(token, token.value.chars().count())))
+        })
+        .collect()
+}
+
+/// Returns the span of an opening fence that is `width` characters long.
+fn fence_span(token: &Token, width: usize) -> LineSpan {
+    LineSpan {
+        start: span_offset(token.line),
+        end: span_offset(token.line),
+        start_col: span_offset(token.column),
+        end_col: span_offset(token.column + width),
+        bytes: ByteSpan {
+            start: span_offset(token.start_byte),
+            end: span_offset(token.end_byte),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_closed_fences() {
+        let input = "```rust\nlet x = 1;\n```\n:::warning\ntext\n:::";
+
+        assert_eq!(validate_fences(input), Ok(()));
+    }
+
+    #[test]
+    fn test_unclosed_code_block() {
+        let input = "text\n```rust\nlet x = 1;";
+
+        assert_eq!(
+            validate_fences(input),
+            Err(vec![LintIssue {
+                message: "unclosed code block".to_string(),
+                position: LineSpan {
+                    start: 2,
+                    end: 2,
+                    start_col: 1,
+                    end_col: 4,
+                    bytes: ByteSpan { start: 5, end: 8 }
+                }
+            }])
+        );
+    }
+
+    #[test]
+    fn test_unclosed_alert_block() {
+        let input = ":::note\ntext\n:::tip\ntext\n:::";
+
+        assert_eq!(
+            validate_fences(input),
+            Err(vec![LintIssue {
+                message: "unclosed alert block".to_string(),
+                position: LineSpan {
+                    start: 1,
+                    end: 1,
+                    start_col: 1,
+                    end_col: 8,
+                    bytes: ByteSpan { start: 0, end: 7 }
+                }
+            }])
+        );
+    }
+
+    #[test]
+    fn test_unclosed_html_block() {
+        let input = "<div class=\"note\">\n<div>inner</div>\n<br/>\n`<pre>` in code";
+
+        assert_eq!(
+            validate_fences(input),
+            Err(vec![LintIssue {
+                message: "unclosed HTML block `<div>`".to_string(),
+                position: LineSpan {
+                    start: 1,
+                    end: 1,
+                    start_col: 1,
+                    end_col: 5,
+                    bytes: ByteSpan { start: 0, end: 4 }
+                }
+            }])
+        );
+        assert_eq!(validate_fences("<details>\ntext\n</details>"), Ok(()));
+        assert_eq!(validate_fences("```\n<div>\n```"), Ok(()));
+    }
+
+    #[test]
+    fn test_unbalanced_markers() {
+        let input = "an *unclosed marker\nan extra] bracket";
+
+        assert_eq!(
+            unbalanced_markers(input),
+            vec![
+                (
+                    '*',
+                    LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 4,
+                        end_col: 5,
+                        bytes: ByteSpan { start: 3, end: 4 }
+                    }
+                ),
+                (
+                    ']',
+                    LineSpan {
+                        start: 2,
+                        end: 2,
+                        start_col: 9,
+                        end_col: 10,
+                        bytes: ByteSpan { start: 28, end: 29 }
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unclosed_code_span_and_bracket() {
+        let input = "`code *with* [text\nsee [link](url) **bold** and ~~gone~~";
+        let markers: Vec<char> = unbalanced_markers(input)
+            .into_iter()
+            .map(|(marker, _)| marker)
+            .collect();
+
+        assert_eq!(markers, vec!['`']);
+    }
+
+    #[test]
+    fn test_balanced_markers() {
+        let input = "# *Title*\n- [ ] `a*b` and [link](url)\n```\n*not closed\n```";
+
+        assert_eq!(unbalanced_markers(input), vec![]);
+    }
+
+    #[test]
+    fn test_image_without_alt() {
+        let input = "![a cat](cat.png)\n\n![](dog.png)";
+        let nodes = crate::parser::build_tree(input);
+
+        assert_eq!(
+            lint_image_alt(&nodes),
+            vec![LintIssue {
+                message: "image `dog.png` has no alt text".to_string(),
+                position: LineSpan {
+                    start: 3,
+                    end: 3,
+                    start_col: 1,
+                    end_col: 13,
+                    bytes: ByteSpan { start: 19, end: 31 }
+                }
+            }]
+        );
+    }
+}
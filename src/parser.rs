@@ -1,11 +1,18 @@
-use std::{collections::HashMap, vec};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    time::{Duration, Instant},
+    vec,
+};
 
 use crate::{
-    lexer::lex,
+    html::unique_slug,
+    lexer::{column_after, lex, lex_with_tab_width},
     token::{Token, TokenType},
     tree::{
-        Alert, AlertType, Bold, Eol, Header, Italic, LineSpan, Node, Paragraph, Positioned, Text,
-        UnorderedList, Whitespace,
+        plain_text, span_offset, walk_with_depth, Alert, AlertType, Alignment, Bold, ByteSpan,
+        CodeBlock, Eol, FootnoteDef, FootnoteRef, FrontMatter, HardBreak, Header, HeadingEntry,
+        Image, InlineCode, Italic, LineSpan, Link, List, ListItem, Node, OrderedList, Paragraph,
+        Positioned, Strikethrough, Table, TableOfContents, Text, UnorderedList, Whitespace,
     },
 };
 
@@ -17,39 +24,89 @@ use crate::{
 /// # Fields
 /// - `tokens`: A mutable reference to a vector of tokens to be managed.
 /// - `index`: The current position in the token stream.
+/// - `options`: The options that change how the tokens are parsed.
+/// - `diagnostics`: The problems found while parsing so far.
+/// - `link_definitions`: The `[label]: url` definitions that reference links resolve against.
+/// - `footnote_ids`: The normalized ids of the `[^id]: text` footnote definitions.
+/// - `setext_checked_until`: The index up to which paragraph lines are known not to be
+///   followed by a setext underline.
+///
+/// This structure is commonly used in parsers to process a list of tokens.
+/// Its navigation methods are public, so other parsers can be built on the same tokens:
+///
+/// ```
+/// use twigmd::{lex, parser::TokenStream, TokenType};
+///
+/// let mut tokens = lex("- item");
+/// let mut stream = TokenStream::new(&mut tokens);
+/// assert_eq!(stream.peek().map(|token| &token.token_type), Some(&TokenType::UnorderedList));
+/// assert_eq!(stream.is_next_list(), Some(0));
 ///
-/// This structure is commonly used in parsers to process a list of tokens
-struct TokenStream<'a> {
+/// stream.next();
+/// assert_eq!(stream.peek().map(|token| token.value.as_str()), Some("item"));
+/// stream.back();
+/// assert_eq!(stream.get(1), stream.tokens().get(1));
+/// ```
+pub struct TokenStream<'a> {
     tokens: &'a mut Vec<Token>,
     index: usize,
+    options: ParseOptions,
+    diagnostics: Vec<Diagnostic>,
+    link_definitions: LinkDefinitions,
+    footnote_ids: BTreeSet<String>,
+    setext_checked_until: usize,
 }
 
 impl<'a> TokenStream<'a> {
     /// Creates a new `TokenStream` instance.
-    fn new(tokens: &'a mut Vec<Token>) -> Self {
-        Self { tokens, index: 0 }
+    ///
+    /// The link and footnote definitions of the whole document are collected up front,
+    /// so a reference can be resolved before its definition is reached.
+    pub fn new(tokens: &'a mut Vec<Token>) -> Self {
+        let link_definitions = collect_link_definitions(tokens);
+        let footnote_ids = collect_footnote_ids(tokens);
+        Self {
+            tokens,
+            index: 0,
+            options: ParseOptions::default(),
+            diagnostics: vec![],
+            link_definitions,
+            footnote_ids,
+            setext_checked_until: 0,
+        }
+    }
+
+    /// Returns all of the tokens, regardless of the current position.
+    pub fn tokens(&self) -> &[Token] {
+        self.tokens
+    }
+
+    /// Returns the index of the current token.
+    pub fn index(&self) -> usize {
+        self.index
     }
 
     /// Returns the designated token.
-    fn get(&self, ix: usize) -> Option<&Token> {
+    pub fn get(&self, ix: usize) -> Option<&Token> {
         self.tokens.get(ix)
     }
 
     /// Returns the current token.
-    fn peek(&self) -> Option<&Token> {
+    pub fn peek(&self) -> Option<&Token> {
         self.tokens.get(self.index)
     }
 
     /// Returns the current token and advances the index to the next token in the stream.
-    fn next(&mut self) -> Option<&Token> {
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<&Token> {
         let token = self.tokens.get(self.index);
         self.index += 1;
         token
     }
 
-    /// Moves the index back one token.
-    fn back(&mut self) {
-        self.index -= 1;
+    /// Moves the index back one token, staying at the first token.
+    pub fn back(&mut self) {
+        self.index = self.index.saturating_sub(1);
     }
 
     /// Replaces the current token with the given token.
@@ -57,17 +114,36 @@ impl<'a> TokenStream<'a> {
         self.tokens[self.index] = token;
     }
 
+    /// Records a warning about malformed input found on the given line.
+    fn warn(&mut self, message: String, line: usize) {
+        self.diagnostics.push(Diagnostic {
+            message,
+            line,
+            severity: Severity::Warning,
+        });
+    }
+
+    /// Returns the width of a whitespace token, where a tab advances to the next tab stop.
+    fn indent_width(&self, token: &Token) -> usize {
+        column_after(token.column, &token.value, self.options.tab_width) - token.column
+    }
+
     /// Determines if the next token is a list element and returns its nesting level.
-    fn is_next_list(&self) -> Option<usize> {
+    ///
+    /// The level is the width of the indentation, where a tab counts as `tab_width` spaces,
+    /// divided by `spaces_per_level` and rounded down, so a partial level does not nest further.
+    pub fn is_next_list(&self) -> Option<usize> {
         let mut nest = 0;
         let mut ix = self.index;
 
         while let Some(token) = self.get(ix) {
             if token.token_type == TokenType::Whitespace {
-                nest += 1;
+                nest += self.indent_width(token);
                 ix += 1;
-            } else if token.token_type == TokenType::UnorderedList {
-                return Some(nest);
+            } else if token.token_type == TokenType::UnorderedList
+                || token.token_type == TokenType::OrderedList
+            {
+                return Some(nest / self.options.spaces_per_level.max(1));
             } else {
                 break;
             }
@@ -76,10 +152,317 @@ impl<'a> TokenStream<'a> {
     }
 }
 
+/// The url and optional title of each link definition, keyed by its normalized label.
+///
+/// A sorted map, so that nothing derived from the definitions depends on hash order.
+type LinkDefinitions = BTreeMap<String, (String, Option<String>)>;
+
+/// Options that change how the tokens are turned into nodes.
+#[derive(Debug, Clone)]
+struct ParseOptions {
+    max_header_level: usize,
+    strict_emphasis: bool,
+    tab_width: usize,
+    table_of_contents: bool,
+    group_lists: bool,
+    ordered_lists: bool,
+    list_indent: Option<usize>,
+    spaces_per_level: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            max_header_level: 6,
+            strict_emphasis: false,
+            tab_width: 4,
+            table_of_contents: false,
+            group_lists: false,
+            ordered_lists: true,
+            list_indent: None,
+            // One space per level for older documents, see `Parser::spaces_per_level`
+            spaces_per_level: 1,
+        }
+    }
+}
+
+/// A Markdown parser with configurable behavior.
+///
+/// # Example
+/// ```
+/// use twigmd::parser::Parser;
+///
+/// let parser = Parser::new().max_header_level(3).strict_emphasis(true);
+/// let tree = parser.parse("#### not a header");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Parser {
+    options: ParseOptions,
+}
+
+impl Parser {
+    /// Creates a parser with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the deepest header level. Headers with more `#` markers are parsed as paragraphs.
+    ///
+    /// Defaults to 6.
+    pub fn max_header_level(mut self, level: usize) -> Self {
+        self.options.max_header_level = level;
+        self
+    }
+
+    /// Sets how unclosed `*` and `**` markers are handled.
+    ///
+    /// When `false` (the default), an unclosed marker is kept as literal text.
+    /// When `true`, it is dropped and only the text that follows it is kept.
+    pub fn strict_emphasis(mut self, strict: bool) -> Self {
+        self.options.strict_emphasis = strict;
+        self
+    }
+
+    /// Sets how many columns apart the tab stops are.
+    ///
+    /// A tab advances the column to the next tab stop, in the positions of the nodes
+    /// and in the indentation of a list item, whose columns are then divided by
+    /// `spaces_per_level` like spaces are. A width of 1 counts each tab as one column.
+    /// Defaults to 4, so `\t- item` nests like `    - item`.
+    pub fn tab_width(mut self, width: usize) -> Self {
+        self.options.tab_width = width;
+        self
+    }
+
+    /// Sets whether a `[TOC]` paragraph is replaced with a table of contents.
+    ///
+    /// The table lists every header of the document, with the anchors that
+    /// `RenderOptions::header_anchors` gives them. Defaults to `false`.
+    pub fn table_of_contents(mut self, enabled: bool) -> Self {
+        self.options.table_of_contents = enabled;
+        self
+    }
+
+    /// Sets whether runs of sibling list items are grouped into a `List` node.
+    ///
+    /// When `true`, consecutive `-` items (or numbered items) become `ListItem`s
+    /// of one `List`, and nested items are grouped the same way under their parent.
+    /// Defaults to `false`, which keeps one `UnorderedList` or `OrderedList` per item.
+    pub fn group_lists(mut self, enabled: bool) -> Self {
+        self.options.group_lists = enabled;
+        self
+    }
+
+    /// Sets whether `1. ` at the start of a line begins an ordered list item.
+    ///
+    /// When `false`, the number is kept as text, for documents where such lines are prose.
+    /// Defaults to `true`.
+    pub fn ordered_lists(mut self, enabled: bool) -> Self {
+        self.options.ordered_lists = enabled;
+        self
+    }
+
+    /// Sets how far past its marker a line must be indented to continue a list item.
+    ///
+    /// An indented line that is not a list item, such as `  more` under `- item`,
+    /// becomes a continuation `Paragraph` in the item's nodes. By default the line
+    /// must be indented to the column the item's text starts at.
+    pub fn list_indent(mut self, width: usize) -> Self {
+        self.options.list_indent = Some(width);
+        self
+    }
+
+    /// Sets how many columns of indentation make one level of list nesting.
+    ///
+    /// The division rounds down, and an item nests at most one level below its parent,
+    /// so `  - item`, `   - item` and `    - item` under a top-level item are all at level 1.
+    /// Items indented alike under the same parent are siblings, however wide the indentation.
+    ///
+    /// Defaults to 1 rather than CommonMark's 2: documents written for earlier versions nest
+    /// ` - item` under `- item` with a single space, and a default of 2 would flatten them.
+    /// Since an item never nests more than one level below its parent, a wider indentation
+    /// still gives the expected tree with the default.
+    pub fn spaces_per_level(mut self, spaces: usize) -> Self {
+        self.options.spaces_per_level = spaces;
+        self
+    }
+
+    /// Parses a Markdown string and builds its tree structure.
+    pub fn parse(&self, input: &str) -> Vec<Node> {
+        let mut tokens = lex_with_tab_width(input, self.options.tab_width);
+        if !self.options.ordered_lists {
+            tokens = ordered_list_markers_as_text(tokens);
+        }
+        let mut stream = TokenStream::new(&mut tokens);
+        stream.options = self.options.clone();
+        let mut nodes = parse(&mut stream);
+        if self.options.table_of_contents {
+            insert_table_of_contents(&mut nodes);
+        }
+        if self.options.group_lists {
+            nodes = group_lists(nodes);
+        }
+        nodes
+    }
+}
+
+/// Splits every ordered list marker into the text of its number and the space after it,
+/// which are the tokens the line would have without the marker.
+fn ordered_list_markers_as_text(tokens: Vec<Token>) -> Vec<Token> {
+    let mut text_tokens = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        if token.token_type != TokenType::OrderedList {
+            text_tokens.push(token);
+            continue;
+        }
+        let number = format!("{}.", token.value);
+        text_tokens.push(Token {
+            token_type: TokenType::Text,
+            column: token.column,
+            start_byte: token.start_byte,
+            end_byte: token.end_byte - 1,
+            line: token.line,
+            value: number.clone(),
+        });
+        text_tokens.push(Token {
+            token_type: TokenType::Whitespace,
+            value: " ".to_string(),
+            line: token.line,
+            column: token.column + number.len(),
+            start_byte: token.end_byte - 1,
+            end_byte: token.end_byte,
+        });
+    }
+    text_tokens
+}
+
+/// Replaces every top-level `[TOC]` paragraph with a table of the document's headers.
+fn insert_table_of_contents(nodes: &mut [Node]) {
+    let mut slugs = HashMap::new();
+    let mut entries: Vec<HeadingEntry> = vec![];
+    walk_with_depth(nodes, |_, node| {
+        if let Node::Header(header) = node {
+            let text = plain_text(&header.nodes);
+            entries.push(HeadingEntry {
+                level: header.level,
+                anchor: unique_slug(&mut slugs, &text),
+                text,
+            });
+        }
+    });
+
+    for node in nodes.iter_mut() {
+        let Node::Paragraph(paragraph) = node else {
+            continue;
+        };
+        if plain_text(&paragraph.nodes) == "[TOC]" {
+            let position = paragraph.position.clone();
+            *node = Node::TableOfContents(TableOfContents {
+                entries: entries.clone(),
+                position,
+            });
+        }
+    }
+}
+
+/// Groups runs of sibling list items into `List` nodes, recursing into their children.
+fn group_lists(nodes: Vec<Node>) -> Vec<Node> {
+    let mut grouped: Vec<Node> = vec![];
+    for node in nodes {
+        let (ordered, start, item) = match node {
+            Node::UnorderedList(list) => (
+                false,
+                1,
+                ListItem {
+                    checked: list.checked,
+                    nodes: list.nodes,
+                    children: group_lists(list.children),
+                    position: list.position,
+                },
+            ),
+            Node::OrderedList(list) => (
+                true,
+                list.start,
+                ListItem {
+                    checked: None,
+                    nodes: list.nodes,
+                    children: group_lists(list.children),
+                    position: list.position,
+                },
+            ),
+            Node::Alert(mut alert) => {
+                alert.nodes = group_lists(alert.nodes);
+                grouped.push(Node::Alert(alert));
+                continue;
+            }
+            node => {
+                grouped.push(node);
+                continue;
+            }
+        };
+
+        let loose = item
+            .nodes
+            .iter()
+            .any(|node| matches!(node, Node::Paragraph(_)));
+        match grouped.last_mut() {
+            Some(Node::List(list)) if list.ordered == ordered => {
+                list.position = join_spans(&list.position, &item.position);
+                list.loose |= loose;
+                list.items.push(Node::ListItem(item));
+            }
+            _ => grouped.push(Node::List(List {
+                ordered,
+                start,
+                loose,
+                position: item.position.clone(),
+                items: vec![Node::ListItem(item)],
+            })),
+        }
+    }
+    grouped
+}
+
+/// Returns the span of the given token in the original document.
+fn token_span(token: &Token) -> LineSpan {
+    LineSpan {
+        start: span_offset(token.line),
+        end: span_offset(token.line),
+        start_col: span_offset(token.column),
+        end_col: span_offset(token.column + token.value.chars().count()),
+        bytes: ByteSpan {
+            start: span_offset(token.start_byte),
+            end: span_offset(token.end_byte),
+        },
+    }
+}
+
+/// Returns the span of a whitespace token, which ends at the tab stop after a trailing tab.
+fn whitespace_span(token: &Token, tab_width: usize) -> LineSpan {
+    let mut span = token_span(token);
+    span.end_col = span_offset(column_after(token.column, &token.value, tab_width));
+    span
+}
+
+/// Returns the span from the start of `first` to the end of `last`.
+fn join_spans(first: &LineSpan, last: &LineSpan) -> LineSpan {
+    LineSpan {
+        start: first.start,
+        end: last.end,
+        start_col: first.start_col,
+        end_col: last.end_col,
+        bytes: ByteSpan {
+            start: first.bytes.start,
+            end: last.bytes.end,
+        },
+    }
+}
+
 /// Returns the position of the given node in the orginal document.
 fn get_position(node: &Node) -> Option<&LineSpan> {
     match node {
         Node::UnorderedList(list) => Some(list.position()),
+        Node::OrderedList(list) => Some(list.position()),
         _ => None,
     }
 }
@@ -107,82 +490,382 @@ fn get_position(node: &Node) -> Option<&LineSpan> {
 ///   for other text formats.
 /// - The structure of the returned tree depends on the implementation details of
 ///   the `parse` function and its handling of Markdown tokens.
+/// - Blank input yields no paragraphs: an empty or whitespace-only string gives an
+///   empty tree, and each blank line only contributes its `Eol` node.
+/// - This is a shorthand for `Parser::new().parse(input)`.
 pub fn build_tree(input: &str) -> Vec<Node> {
+    Parser::new().parse(input)
+}
+
+/// Parses a Markdown string like `build_tree`, grouping sibling list items into `List` nodes.
+///
+/// This is a shorthand for `Parser::new().group_lists(true).parse(input)`.
+pub fn build_tree_grouped(input: &str) -> Vec<Node> {
+    Parser::new().group_lists(true).parse(input)
+}
+
+/// Parses a Markdown string lazily, yielding one top-level node at a time.
+///
+/// Each block is only parsed when the iterator is advanced, so a consumer that only
+/// needs the first few blocks (e.g. the title) skips parsing the rest of the document.
+/// Collecting the iterator yields the same nodes as `build_tree`.
+///
+/// Only the parsing is lazy. The whole input is still lexed, and scanned for link and
+/// footnote definitions, before the first block is returned, because a reference can
+/// come before its definition and is resolved as soon as its block is parsed.
+pub fn parse_iter(input: &str) -> impl Iterator<Item = Node> {
+    block_iter(input)
+}
+
+fn block_iter(input: &str) -> BlockIter {
+    let tokens = lex(input);
+    BlockIter {
+        link_definitions: collect_link_definitions(&tokens),
+        footnote_ids: collect_footnote_ids(&tokens),
+        tokens,
+        index: 0,
+        options: ParseOptions::default(),
+        setext_checked_until: 0,
+    }
+}
+
+/// The iterator returned by `parse_iter`, which owns the tokens it parses.
+struct BlockIter {
+    tokens: Vec<Token>,
+    index: usize,
+    options: ParseOptions,
+    link_definitions: LinkDefinitions,
+    footnote_ids: BTreeSet<String>,
+    setext_checked_until: usize,
+}
+
+impl Iterator for BlockIter {
+    type Item = Node;
+
+    fn next(&mut self) -> Option<Node> {
+        // The options and maps are moved into the stream and back, so that they are not copied per block
+        let mut stream = TokenStream {
+            tokens: &mut self.tokens,
+            index: self.index,
+            options: std::mem::take(&mut self.options),
+            diagnostics: vec![],
+            link_definitions: std::mem::take(&mut self.link_definitions),
+            footnote_ids: std::mem::take(&mut self.footnote_ids),
+            setext_checked_until: self.setext_checked_until,
+        };
+        let node = parse_block(&mut stream);
+        self.index = stream.index;
+        self.setext_checked_until = stream.setext_checked_until;
+        self.options = stream.options;
+        self.link_definitions = stream.link_definitions;
+        self.footnote_ids = stream.footnote_ids;
+        node
+    }
+}
+
+/// Runtime statistics collected by `build_tree_with_metrics`.
+#[derive(Debug, Clone)]
+pub struct ParseMetrics {
+    /// Number of tokens produced by the lexer.
+    pub token_count: usize,
+    /// Number of nodes in the tree, including nested ones.
+    pub node_count: usize,
+    /// Time spent lexing the input.
+    pub lex_time: Duration,
+    /// Time spent building the tree from the tokens.
+    pub parse_time: Duration,
+    /// Deepest nesting level of the tree. Top-level nodes are at depth 1.
+    pub max_depth: usize,
+}
+
+/// Parses a Markdown string like `build_tree`, and also reports how the parse went.
+///
+/// This is meant for logging and performance debugging in applications.
+pub fn build_tree_with_metrics(input: &str) -> (Vec<Node>, ParseMetrics) {
+    let lex_start = Instant::now();
+    let mut tokens = lex(input);
+    let lex_time = lex_start.elapsed();
+    let token_count = tokens.len();
+
+    let parse_start = Instant::now();
+    let mut stream = TokenStream::new(&mut tokens);
+    let nodes = parse(&mut stream);
+    let parse_time = parse_start.elapsed();
+
+    let metrics = ParseMetrics {
+        token_count,
+        node_count: count_nodes(&nodes),
+        lex_time,
+        parse_time,
+        max_depth: max_depth(&nodes),
+    };
+    (nodes, metrics)
+}
+
+/// How serious a `Diagnostic` is.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem found in the input while parsing it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Diagnostic {
+    pub message: String,
+    pub line: usize,
+    pub severity: Severity,
+}
+
+/// Parses a Markdown string like `build_tree`, and also reports the malformed input it recovered from.
+///
+/// Unclosed emphasis markers, unclosed code blocks, headers deeper than the maximum level and characters
+/// the lexer does not recognize are reported as warnings.
+pub fn build_tree_with_diagnostics(input: &str) -> (Vec<Node>, Vec<Diagnostic>) {
     let mut tokens = lex(input);
     let mut stream = TokenStream::new(&mut tokens);
-    parse(&mut stream)
+    let nodes = parse(&mut stream);
+    (nodes, stream.diagnostics)
+}
+
+/// Counts the given nodes and all of their nested nodes.
+fn count_nodes(nodes: &[Node]) -> usize {
+    nodes
+        .iter()
+        .map(|node| 1 + node.nested_slices().map(count_nodes).sum::<usize>())
+        .sum()
+}
+
+/// Returns the deepest nesting level of the given nodes.
+fn max_depth(nodes: &[Node]) -> usize {
+    nodes
+        .iter()
+        .map(|node| 1 + node.nested_slices().map(max_depth).max().unwrap_or(0))
+        .max()
+        .unwrap_or(0)
 }
 
 fn parse(stream: &mut TokenStream) -> Vec<Node> {
     let mut nodes: Vec<Node> = vec![];
-    while let Some(token) = stream.peek() {
+    while let Some(node) = parse_block(stream) {
+        nodes.push(node);
+    }
+    nodes
+}
+
+/// Parses the block starting at the current token.
+///
+/// Returns `None` when there are no tokens left.
+fn parse_block(stream: &mut TokenStream) -> Option<Node> {
+    if stream.index == 0 {
+        if let Some(front_matter) = parse_front_matter(stream) {
+            return Some(front_matter);
+        }
+    }
+    if let Some(footnote_def) = parse_footnote_definition(stream) {
+        return Some(footnote_def);
+    }
+    // Link definitions were collected up front and are not part of the tree
+    if let Some((.., line_end)) = link_definition_at(stream.tokens, stream.index) {
+        stream.index = line_end;
+        return parse_block(stream);
+    }
+    // A line of only whitespace is blank, so only its line break is kept
+    if is_blank_line(stream) {
+        skip_indent(stream);
+    }
+
+    let token = stream.peek()?;
+    let node = match token.token_type {
+        TokenType::Header => parse_header(stream),
+        TokenType::UnorderedList => parse_unordered_list(stream, 0, 0), // root level
+        TokenType::OrderedList => parse_ordered_list(stream, 0, 0),     // root level
+        TokenType::Text
+        | TokenType::Whitespace
+        | TokenType::Italic
+        | TokenType::Bold
+        | TokenType::Strikethrough => {
+            parse_table(stream).unwrap_or_else(|| parse_setext_header(stream))
+        }
+        TokenType::BlockQuote => parse_quote(stream),
+        TokenType::CodeBlock => parse_code_block(stream),
+        TokenType::AlertStart => parse_alert(stream),
+        TokenType::Eol => {
+            let node = Node::Eol(Eol {
+                position: token_span(token),
+            });
+            stream.next();
+            node
+        }
+        _ => parse_table(stream).unwrap_or_else(|| parse_setext_header(stream)),
+    };
+    Some(node)
+}
+
+/// Parses a front matter block delimited by `---` lines at the very start of the document.
+///
+/// The lines in between are captured verbatim, without interpreting the YAML.
+/// Returns `None` without consuming anything if the document does not start with `---`
+/// or the block is never closed.
+fn parse_front_matter(stream: &mut TokenStream) -> Option<Node> {
+    let content_start = front_matter_fence_end(stream.tokens, 0)?;
+    // The closing fence is the next line that holds only `---`
+    let mut ix = content_start;
+    let content_end = loop {
+        if let Some(fence_end) = front_matter_fence_end(stream.tokens, ix) {
+            break fence_end;
+        }
+        let line_end = stream.tokens[ix..]
+            .iter()
+            .position(|token| token.token_type == TokenType::Eol)?;
+        ix += line_end + 1;
+    };
+
+    // The line break before the closing fence is not part of the content
+    let raw = join_values(&stream.tokens[content_start..ix.saturating_sub(1).max(content_start)]);
+    let start = token_span(&stream.tokens[0]);
+    let end = token_span(&stream.tokens[ix]);
+    stream.index = content_end;
+    Some(Node::FrontMatter(FrontMatter {
+        raw,
+        position: join_spans(&start, &end),
+    }))
+}
+
+/// Returns the index after the line at `ix` if it holds only a `---` fence.
+fn front_matter_fence_end(tokens: &[Token], ix: usize) -> Option<usize> {
+    let fence = tokens.get(ix)?;
+    if fence.token_type != TokenType::Text || fence.value != "---" {
+        return None;
+    }
+    let mut end = ix + 1;
+    while let Some(token) = tokens.get(end) {
+        end += 1;
         match token.token_type {
-            TokenType::Header => {
-                let node = parse_header(stream);
-                nodes.push(node);
-            }
-            TokenType::UnorderedList => {
-                let node = parse_unordered_list(stream, 0); // root level
-                nodes.push(node);
-            }
-            TokenType::Text | TokenType::Whitespace | TokenType::Italic | TokenType::Bold => {
-                let node = parse_paragraph(stream);
-                nodes.push(node);
-            }
-            TokenType::BlockQuote => {
-                let node = parse_quote(stream);
-                nodes.push(node);
-            }
-            TokenType::Eol => {
-                let node = Node::Eol(Eol {
-                    position: LineSpan {
-                        start: token.line,
-                        end: token.line,
-                    },
-                });
-                nodes.push(node);
-                stream.next();
-            }
-            _ => {
-                let node = parse_paragraph(stream);
-                nodes.push(node);
-            }
+            TokenType::Whitespace => {}
+            TokenType::Eol => break,
+            _ => return None,
         }
     }
-    nodes
+    Some(end)
+}
+
+/// Parses an alert block delimited by `:::<type>` and `:::`.
+///
+/// The body is parsed with the block parsers until the closing marker.
+/// An alert without a closing marker ends at the end of input.
+fn parse_alert(stream: &mut TokenStream) -> Node {
+    let (start, marker) = match stream.peek() {
+        Some(token) => (token.clone(), token.value.to_lowercase()),
+        None => return parse_paragraph(stream),
+    };
+
+    let alert_type = match marker.as_str() {
+        "" | "note" => AlertType::Note,
+        "tip" => AlertType::Tip,
+        "important" => AlertType::Important,
+        "warning" => AlertType::Warning,
+        "caution" => AlertType::Caution,
+        // If the alert type is unknown, treat the marker line as a Paragraph
+        _ => {
+            let alert_text_token = Token {
+                token_type: TokenType::Text,
+                value: format!(":::{}", marker),
+                line: start.line,
+                column: start.column,
+                start_byte: start.start_byte,
+                end_byte: start.end_byte,
+            };
+            stream.replace(alert_text_token);
+            return parse_paragraph(stream);
+        }
+    };
+    stream.next();
+    skip_eol(stream);
+
+    let start = token_span(&start);
+    let mut end = start.clone();
+    let mut nodes: Vec<Node> = vec![];
+    while let Some(token) = stream.peek() {
+        if token.token_type == TokenType::AlertEnd {
+            end = token_span(token);
+            stream.next();
+            skip_eol(stream);
+            break;
+        }
+        if let Some(node) = parse_block(stream) {
+            end = node.position().clone();
+            nodes.push(node);
+        }
+    }
+
+    Node::Alert(Alert {
+        alert_type,
+        nodes,
+        position: join_spans(&start, &end),
+    })
+}
+
+/// Checks whether the current line holds nothing but whitespace.
+fn is_blank_line(stream: &TokenStream) -> bool {
+    stream
+        .peek()
+        .is_some_and(|token| token.token_type == TokenType::Whitespace)
+        && stream
+            .get(stream.index + 1)
+            .is_none_or(|token| token.token_type == TokenType::Eol)
+}
+
+/// Consumes the current token if it is a line break.
+fn skip_eol(stream: &mut TokenStream) {
+    if let Some(token) = stream.peek() {
+        if token.token_type == TokenType::Eol {
+            stream.next();
+        }
+    }
+}
+
+/// Consumes the whitespace tokens before a nested list marker.
+fn skip_indent(stream: &mut TokenStream) {
+    while let Some(token) = stream.peek() {
+        if token.token_type != TokenType::Whitespace {
+            break;
+        }
+        stream.next();
+    }
 }
 
 fn parse_quote(stream: &mut TokenStream) -> Node {
     let start = if let Some(token) = stream.peek() {
-        token.line
+        token_span(token)
     } else {
-        0
+        LineSpan::default()
     };
 
     // in case of alert
     if let Some(alert_type) = is_alert(stream) {
         let mut nodes: Vec<Node> = vec![];
         while is_next_line_quoted(stream) {
-            if let Some(node) = nodes.last() {
-                nodes.push(Node::Eol(Eol {
-                    position: LineSpan {
-                        start: node.position().start,
-                        end: node.position().end,
-                    },
-                }));
+            if !nodes.is_empty() {
+                // The line break is 3 tokens before the quoted content ("\n", ">", " ")
+                if let Some(eol_token) = stream.get(stream.index - 3) {
+                    nodes.push(Node::Eol(Eol {
+                        position: token_span(eol_token),
+                    }));
+                }
             }
             nodes.extend(parse_line(stream));
             stream.back(); // because parse_line uses stream.next()
         }
         let end = if let Some(nodes_last) = nodes.last() {
-            nodes_last.position().end
+            nodes_last.position().clone()
         } else {
-            start
+            start.clone()
         };
         return Node::Alert(Alert {
             alert_type,
             nodes,
-            position: { LineSpan { start, end } },
+            position: join_spans(&start, &end),
         });
     }
 
@@ -246,48 +929,142 @@ fn is_alert(stream: &mut TokenStream) -> Option<AlertType> {
     None
 }
 
-fn parse_unordered_list(stream: &mut TokenStream, cur_nest: usize) -> Node {
+fn parse_unordered_list(stream: &mut TokenStream, cur_nest: usize, indent: usize) -> Node {
+    let checked = task_checkbox(stream);
+    let (mut nodes, children, position) = parse_list_item(stream, cur_nest, indent);
+    if checked.is_some() {
+        // Strip the checkbox and the whitespace after it, keeping only the label
+        let len = match nodes.get(3) {
+            Some(Node::Whitespace(_)) => 4,
+            _ => 3,
+        };
+        nodes.drain(..len.min(nodes.len()));
+    }
+
+    Node::UnorderedList(UnorderedList {
+        level: cur_nest,
+        checked,
+        nodes,
+        children,
+        position,
+    })
+}
+
+/// Checks whether the list item starting at the current marker is a task list item.
+///
+/// Returns whether its checkbox is checked, or `None` if the item does not start
+/// with `[ ]`, `[x]` or `[X]` followed by whitespace or the end of the line.
+fn task_checkbox(stream: &TokenStream) -> Option<bool> {
+    let tokens = stream.tokens.get(stream.index + 1..stream.index + 4)?;
+    if tokens[0].token_type != TokenType::SquareBracketOpen
+        || tokens[2].token_type != TokenType::SquareBracketClose
+    {
+        return None;
+    }
+    let checked = match (&tokens[1].token_type, tokens[1].value.as_str()) {
+        (TokenType::Whitespace, _) => false,
+        (TokenType::Text, "x" | "X") => true,
+        _ => return None,
+    };
+    match stream.get(stream.index + 4) {
+        Some(token) if !matches!(token.token_type, TokenType::Whitespace | TokenType::Eol) => None,
+        _ => Some(checked),
+    }
+}
+
+fn parse_ordered_list(stream: &mut TokenStream, cur_nest: usize, indent: usize) -> Node {
+    let start = match stream.peek() {
+        Some(token) => token.value.parse().unwrap_or(1),
+        None => 1,
+    };
+    let (nodes, children, position) = parse_list_item(stream, cur_nest, indent);
+
+    Node::OrderedList(OrderedList {
+        level: cur_nest,
+        start,
+        nodes,
+        children,
+        position,
+    })
+}
+
+/// Parses a nested list of either kind, starting from its list marker.
+fn parse_list(stream: &mut TokenStream, cur_nest: usize, indent: usize) -> Node {
+    match stream.peek() {
+        Some(token) if token.token_type == TokenType::OrderedList => {
+            parse_ordered_list(stream, cur_nest, indent)
+        }
+        _ => parse_unordered_list(stream, cur_nest, indent),
+    }
+}
+
+/// Parses the content of a list item and its nested lists, starting from the list marker.
+///
+/// `cur_nest` is the item's level, while `indent` is the level its marker is indented to,
+/// as returned by `TokenStream::is_next_list()`. Only a list indented deeper than `indent`
+/// becomes a child, so siblings indented past one level stay siblings.
+///
+/// Returns the item's nodes, its children and its position.
+fn parse_list_item(
+    stream: &mut TokenStream,
+    cur_nest: usize,
+    indent: usize,
+) -> (Vec<Node>, Vec<Node>, LineSpan) {
     let mut nodes: Vec<Node> = vec![];
     let mut children: Vec<Node> = vec![];
-    let mut start: usize = 0;
-    let mut end: usize = 0;
+    let mut start = LineSpan::default();
+    let mut end = LineSpan::default();
+    let mut content_offset = 0;
 
     while let Some(token) = stream.peek() {
+        // A nested list stops at the start of a line, where the item goes on
+        // only with another nested list or a continuation paragraph
+        let is_line_start =
+            stream.index > 0 && stream.tokens[stream.index - 1].token_type == TokenType::Eol;
+        if !children.is_empty() && is_line_start && stream.is_next_list().is_none() {
+            match parse_list_continuation(stream, content_offset) {
+                Some(paragraph) => {
+                    end = paragraph.position().clone();
+                    nodes.push(paragraph);
+                    continue;
+                }
+                None => break,
+            }
+        }
         match token.token_type {
-            TokenType::UnorderedList => {
+            TokenType::UnorderedList | TokenType::OrderedList => {
                 // If the next line contains a list element without nesting, terminate parsing the list here.
                 if !nodes.is_empty() {
                     break;
                 }
                 // Parsing starts here.
-                start = token.line;
-                end = token.line;
+                start = token_span(token);
+                end = token_span(token);
+                // Continuation lines are indented to the item's text, right after the marker,
+                // unless `list_indent` sets their indentation
+                let width = stream
+                    .options
+                    .list_indent
+                    .unwrap_or(token.end_byte - token.start_byte);
+                content_offset = token.column - 1 + width;
                 stream.next();
             }
             TokenType::Whitespace => {
-                if let Some(nest) = {
-                    let list_check = &stream.is_next_list();
-                    *list_check
-                } {
-                    if nest > cur_nest {
-                        for _ in 0..nest {
-                            stream.next();
-                        }
-                        let child = parse_unordered_list(stream, nest);
+                if let Some(nest) = stream.is_next_list() {
+                    if nest > indent {
+                        skip_indent(stream);
+                        let child = parse_list(stream, cur_nest + 1, nest);
                         if let Some(position) = get_position(&child) {
-                            end = position.end
+                            end = position.clone()
                         }
                         children.push(child);
                     } else {
                         break;
                     }
                 } else {
-                    end = token.line;
+                    end = whitespace_span(token, stream.options.tab_width);
                     nodes.push(Node::Whitespace(Whitespace {
-                        position: LineSpan {
-                            start: token.line,
-                            end: token.line,
-                        },
+                        position: end.clone(),
                     }));
                     stream.next();
                 }
@@ -295,19 +1072,34 @@ fn parse_unordered_list(stream: &mut TokenStream, cur_nest: usize) -> Node {
 
             // Check if the next line contains a nested UnorderedList elemet
             TokenType::Eol => {
+                // A hard break at the end of the line joins the next line to the item's text
+                if children.is_empty()
+                    && is_list_continuation_line(stream, stream.index + 1, content_offset)
+                {
+                    if let Some(hard_break) = take_hard_break(&mut nodes) {
+                        end = hard_break.position().clone();
+                        nodes.push(hard_break);
+                        stream.next();
+                        skip_indent(stream);
+                        continue;
+                    }
+                }
+                if let Some(paragraph) = parse_list_continuation(stream, content_offset) {
+                    end = paragraph.position().clone();
+                    nodes.push(paragraph);
+                    continue;
+                }
                 stream.next(); // Move one step forward from current Eol token
                 if let Some(token) = stream.peek() {
                     if token.token_type == TokenType::Whitespace {
                         // If the next list is a child element, add it to children
                         if let Some(nest) = stream.is_next_list() {
-                            if nest > cur_nest {
-                                // Move forward by the number of whitespace tokens counted, so it becomes the root element in recursive `parse_unordered_list()`
-                                for _ in 0..nest {
-                                    stream.next();
-                                }
-                                let child = parse_unordered_list(stream, nest);
+                            if nest > indent {
+                                // Skip the indentation, so the marker becomes the root element in recursive `parse_list()`
+                                skip_indent(stream);
+                                let child = parse_list(stream, cur_nest + 1, nest);
                                 if let Some(position) = get_position(&child) {
-                                    end = position.end
+                                    end = position.clone()
                                 }
                                 children.push(child);
                             } else {
@@ -325,30 +1117,265 @@ fn parse_unordered_list(stream: &mut TokenStream, cur_nest: usize) -> Node {
             }
             // Save the content of the current list element as Text in nodes
             _ => {
-                end = token.line;
+                end = token_span(token);
                 nodes.push(Node::Text(Text {
                     value: token.value.to_string(),
-                    position: LineSpan {
-                        start: token.line,
-                        end: token.line,
-                    },
+                    position: token_span(token),
                 }));
                 stream.next();
             }
         }
     }
 
-    Node::UnorderedList(UnorderedList {
-        level: cur_nest,
-        nodes,
-        children,
-        position: LineSpan { start, end },
-    })
+    (nodes, children, join_spans(&start, &end))
 }
 
-/// Converts the tokens until the end of the line into nodes
-fn parse_line(stream: &mut TokenStream) -> Vec<Node> {
-    let mut nodes: Vec<Node> = vec![];
+/// Parses a paragraph that continues a list item on the following lines, starting from a line break.
+///
+/// The paragraph may follow the item directly or after blank lines. Every line of it must be
+/// indented at least to `content_offset`. Returns `None` without consuming anything otherwise.
+fn parse_list_continuation(stream: &mut TokenStream, content_offset: usize) -> Option<Node> {
+    let mut ix = stream.index;
+    while stream.get(ix)?.token_type == TokenType::Eol {
+        ix += 1;
+    }
+    if !is_list_continuation_line(stream, ix, content_offset) {
+        return None;
+    }
+
+    stream.index = ix;
+    let mut nodes: Vec<Node> = vec![];
+    loop {
+        skip_indent(stream);
+        nodes.extend(parse_line(stream));
+        let eol = match stream.get(stream.index - 1) {
+            Some(token) if token.token_type == TokenType::Eol => token_span(token),
+            _ => break,
+        };
+        // Leave the line break for the list item, unless the paragraph continues
+        if !is_list_continuation_line(stream, stream.index, content_offset) {
+            stream.back();
+            break;
+        }
+        match take_hard_break(&mut nodes) {
+            Some(hard_break) => nodes.push(hard_break),
+            None => nodes.push(Node::Eol(Eol { position: eol })),
+        }
+    }
+
+    let position = join_spans(nodes.first()?.position(), nodes.last()?.position());
+    Some(Node::Paragraph(Paragraph { nodes, position }))
+}
+
+/// Checks whether the line at `ix` is indented to `content_offset` and holds text.
+fn is_list_continuation_line(stream: &TokenStream, ix: usize, content_offset: usize) -> bool {
+    let Some(indent) = stream.get(ix) else {
+        return false;
+    };
+    indent.token_type == TokenType::Whitespace
+        && stream.indent_width(indent) >= content_offset
+        && stream.get(ix + 1).is_some_and(|token| {
+            !matches!(
+                token.token_type,
+                TokenType::Eol | TokenType::UnorderedList | TokenType::OrderedList
+            )
+        })
+}
+
+/// Parses a fenced code block, starting from the opening fence.
+///
+/// The lexer emits each content line as a single raw `Text` token, so the lines are
+/// collected verbatim. Everything after the opening fence is the info string, and a
+/// closing fence may only be followed by whitespace, so a line like ```` ``` code ````
+/// is kept as content. A code block without a closing fence ends at the end of input.
+fn parse_code_block(stream: &mut TokenStream) -> Node {
+    let mut start = LineSpan::default();
+    let mut end = LineSpan::default();
+    if let Some(token) = stream.next() {
+        start = token_span(token);
+        end = token_span(token);
+    }
+
+    // The rest of the opening fence line is the info string
+    let mut info = String::new();
+    while let Some(token) = stream.next() {
+        if token.token_type == TokenType::Eol {
+            break;
+        }
+        info.push_str(&token.value);
+    }
+    let info = info.trim();
+    let language = if info.is_empty() {
+        None
+    } else {
+        Some(info.to_string())
+    };
+
+    let mut lines: Vec<String> = vec![];
+    let mut line = String::new();
+    let mut is_closed = false;
+    while let Some(token) = stream.next() {
+        end = token_span(token);
+        match token.token_type {
+            TokenType::CodeBlock => {
+                is_closed = true;
+                break;
+            }
+            TokenType::Eol => {
+                lines.push(std::mem::take(&mut line));
+            }
+            _ => {
+                line.push_str(&token.value);
+            }
+        }
+    }
+    if is_closed {
+        // Consume the line break after the closing fence
+        skip_eol(stream);
+    } else {
+        stream.warn("unclosed code block".to_string(), start.start as usize);
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+
+    Node::CodeBlock(CodeBlock {
+        language,
+        value: lines.join("\n"),
+        position: join_spans(&start, &end),
+    })
+}
+
+/// Parses a table: a header row, a delimiter row like `|---|:-:|`, and the body rows below it.
+///
+/// Cells are separated by `|`, and the `|` at the start and end of a row are optional.
+/// The lines are not a table unless the delimiter row has a cell for every header cell.
+/// The body ends at the first line without a `|`, and each row is padded with empty
+/// cells or cut to the number of header cells.
+/// Returns `None` without consuming anything if the lines are not a table.
+fn parse_table(stream: &mut TokenStream) -> Option<Node> {
+    let header_start = stream.index;
+    let header_end = line_end(stream.tokens, header_start);
+    let header_cells = table_cells(stream.tokens, header_start, header_end)?;
+    let delimiter_end = line_end(stream.tokens, header_end + 1);
+    let alignments = table_cells(stream.tokens, header_end + 1, delimiter_end)?
+        .into_iter()
+        .map(|(start, end)| column_alignment(join_values(&stream.tokens[start..end]).trim()))
+        .collect::<Option<Vec<Alignment>>>()?;
+    if alignments.len() != header_cells.len() {
+        return None;
+    }
+
+    let mut row_cells = vec![];
+    let mut last_line_end = delimiter_end;
+    while let Some(cells) = table_cells(
+        stream.tokens,
+        last_line_end + 1,
+        line_end(stream.tokens, last_line_end + 1),
+    ) {
+        row_cells.push(cells);
+        last_line_end = line_end(stream.tokens, last_line_end + 1);
+    }
+
+    let headers = header_cells
+        .into_iter()
+        .map(|(start, end)| parse_inline_range(stream, start, end))
+        .collect();
+    let rows = row_cells
+        .into_iter()
+        .map(|cells| {
+            let mut row: Vec<Vec<Node>> = cells
+                .into_iter()
+                .take(alignments.len())
+                .map(|(start, end)| parse_inline_range(stream, start, end))
+                .collect();
+            row.resize(alignments.len(), vec![]);
+            row
+        })
+        .collect();
+    let start = token_span(&stream.tokens[header_start]);
+    let end = token_span(&stream.tokens[last_line_end - 1]);
+    // The line break after the last row belongs to the table
+    stream.index = (last_line_end + 1).min(stream.tokens.len());
+
+    Some(Node::Table(Table {
+        headers,
+        alignments,
+        rows,
+        position: join_spans(&start, &end),
+    }))
+}
+
+/// Returns the index of the line break ending the line that starts at `ix`,
+/// or the number of tokens if it is the last line.
+fn line_end(tokens: &[Token], ix: usize) -> usize {
+    let ix = ix.min(tokens.len());
+    tokens[ix..]
+        .iter()
+        .position(|token| token.token_type == TokenType::Eol)
+        .map_or(tokens.len(), |offset| ix + offset)
+}
+
+/// Splits the tokens from `start` to `end` into the token ranges of the `|` separated cells.
+///
+/// The whitespace around each cell is left out. Returns `None` if there is no `|`.
+fn table_cells(tokens: &[Token], start: usize, end: usize) -> Option<Vec<(usize, usize)>> {
+    let line = tokens.get(start..end)?;
+    let is_content = |token: &Token| token.token_type != TokenType::Whitespace;
+    let first = line.iter().position(is_content)?;
+    let last = line.iter().rposition(is_content)?;
+    if !line.iter().any(|token| token.token_type == TokenType::Pipe) {
+        return None;
+    }
+
+    // A leading and a trailing `|` do not start or end another cell
+    let mut cell_start = start + first;
+    if line[first].token_type == TokenType::Pipe {
+        cell_start += 1;
+    }
+    let mut cells_end = start + last + 1;
+    if line[last].token_type == TokenType::Pipe && cells_end > cell_start {
+        cells_end -= 1;
+    }
+
+    let mut cells = vec![];
+    let cells_start = cell_start;
+    for ix in cells_start..=cells_end {
+        if ix == cells_end || tokens[ix].token_type == TokenType::Pipe {
+            let mut cell = (cell_start, ix);
+            while cell.0 < cell.1 && !is_content(&tokens[cell.0]) {
+                cell.0 += 1;
+            }
+            while cell.1 > cell.0 && !is_content(&tokens[cell.1 - 1]) {
+                cell.1 -= 1;
+            }
+            cells.push(cell);
+            cell_start = ix + 1;
+        }
+    }
+    Some(cells)
+}
+
+/// Returns the alignment a delimiter row cell like `:--:` sets, or `None` if it is not one.
+fn column_alignment(cell: &str) -> Option<Alignment> {
+    let left = cell.starts_with(':');
+    let right = cell.len() > 1 && cell.ends_with(':');
+    let dashes = &cell[usize::from(left)..cell.len() - usize::from(right)];
+    if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+        return None;
+    }
+    Some(match (left, right) {
+        (true, true) => Alignment::Center,
+        (true, false) => Alignment::Left,
+        (false, true) => Alignment::Right,
+        (false, false) => Alignment::None,
+    })
+}
+
+/// Converts the tokens until the end of the line into nodes
+fn parse_line(stream: &mut TokenStream) -> Vec<Node> {
+    let mut nodes: Vec<Node> = vec![];
+    let tab_width = stream.options.tab_width;
 
     while let Some(token) = stream.next() {
         match token.token_type {
@@ -358,21 +1385,52 @@ fn parse_line(stream: &mut TokenStream) -> Vec<Node> {
             TokenType::Bold => {
                 nodes.extend(parse_bold(stream));
             }
+            TokenType::Strikethrough => {
+                nodes.extend(parse_strikethrough(stream));
+            }
+            TokenType::InlineCode => {
+                nodes.push(parse_inline_code(stream));
+            }
+            TokenType::SquareBracketOpen => {
+                nodes.push(parse_link(stream));
+            }
+            TokenType::Exclamation => {
+                nodes.push(parse_image(stream));
+            }
             TokenType::Whitespace => nodes.push(Node::Whitespace(Whitespace {
-                position: LineSpan {
-                    start: token.line,
-                    end: token.line,
-                },
+                position: whitespace_span(token, tab_width),
             })),
+            // An ordered list marker outside of a list keeps the text it was written as
+            TokenType::OrderedList => {
+                for marker in ordered_list_markers_as_text(vec![token.clone()]) {
+                    nodes.push(match marker.token_type {
+                        TokenType::Whitespace => Node::Whitespace(Whitespace {
+                            position: token_span(&marker),
+                        }),
+                        _ => Node::Text(Text {
+                            position: token_span(&marker),
+                            value: marker.value,
+                        }),
+                    });
+                }
+            }
             // If the token is EOL (end of line), stop parsing
             TokenType::Eol => break,
+            // Characters the lexer does not recognize are kept as text, with a warning
+            TokenType::Unknown => {
+                let node = Node::Text(Text {
+                    value: token.value.to_string(),
+                    position: token_span(token),
+                });
+                let message = format!("unknown character {:?}", token.value);
+                let line = token.line;
+                stream.warn(message, line);
+                nodes.push(node);
+            }
             // For other tokens, treat them as Text nodes
             _ => nodes.push(Node::Text(Text {
                 value: token.value.to_string(),
-                position: LineSpan {
-                    start: token.line,
-                    end: token.line,
-                },
+                position: token_span(token),
             })),
         }
     }
@@ -381,83 +1439,197 @@ fn parse_line(stream: &mut TokenStream) -> Vec<Node> {
 }
 
 fn parse_header(stream: &mut TokenStream) -> Node {
-    let mut nodes: Vec<Node> = vec![];
+    let (header_line, header_column, header_byte) = match stream.peek() {
+        Some(token) => (token.line, token.column, token.start_byte),
+        None => return parse_paragraph(stream),
+    };
 
-    // Validate the header and count header level
-    let mut header_level = 0;
-    let mut header_line = 0;
-    let mut header_position = 0;
+    // Look at the whole run of `#` markers and the token after it before consuming anything
+    let header_level = stream.tokens[stream.index..]
+        .iter()
+        .take_while(|token| token.token_type == TokenType::Header)
+        .count();
+    let next = stream.get(stream.index + header_level).cloned();
+    let has_space = next
+        .as_ref()
+        .is_some_and(|token| token.token_type == TokenType::Whitespace);
 
-    while let Some(token) = stream.next() {
-        match token.token_type {
-            // Increment header level for each `#` token and store its line number
-            TokenType::Header => {
-                header_level += 1;
-                header_line = token.line;
-            }
-            // Stop counting if the token is not a `#`
-            _ => {
-                stream.back();
-                break;
-            }
+    // Decide between a header and a paragraph in one place
+    if has_space && header_level > stream.options.max_header_level {
+        let message = format!(
+            "header level {} exceeds the maximum of {}",
+            header_level, stream.options.max_header_level
+        );
+        stream.warn(message, header_line);
+    }
+    if !has_space || header_level > stream.options.max_header_level {
+        // The markers become a Text token, combined with a text directly following them
+        let mut value = "#".repeat(header_level);
+        let mut end_byte = header_byte + header_level;
+        stream.index += header_level - 1;
+        if let Some(text) = next
+            .as_ref()
+            .filter(|token| token.token_type == TokenType::Text)
+        {
+            value.push_str(&text.value);
+            end_byte = text.end_byte;
+            stream.index += 1;
         }
+        // Replace the current token without modifying the overall token index
+        stream.replace(Token {
+            token_type: TokenType::Text,
+            value,
+            line: header_line,
+            column: header_column,
+            start_byte: header_byte,
+            end_byte,
+        });
+        return parse_paragraph(stream);
     }
 
-    if let Some(token) = stream.peek() {
-        match token.token_type {
-            // If the next token is Whitespace, process it as a valid Header
-            TokenType::Whitespace => {
-                header_position = token.line;
-
-                // If the header level exceeds 6, treat it as a Paragraph instead
-                if header_level > 6 {
-                    let header_text_token = Token {
-                        token_type: TokenType::Text,
-                        value: "#".repeat(header_level),
-                        line: header_line,
-                    };
-                    // Replace the last `#` token with a Text token without modifying the overall token index
-                    stream.back();
-                    stream.replace(header_text_token);
-                    return parse_paragraph(stream);
-                }
-                // Process as a valid Header
-                stream.next();
-                nodes.push(parse_paragraph(stream));
-            }
-            // If the next token is not Whitespace, treat it as a Paragraph
-            _ => {
-                if token.token_type == TokenType::Text {
-                    // Combine the `#` tokens and the text value into a single Paragraph
-                    let value = format!("{}{}", "#".repeat(header_level), token.value);
-                    stream.replace(Token {
-                        token_type: TokenType::Text,
-                        value,
-                        line: header_line,
-                    });
-                    return parse_paragraph(stream);
-                } else {
-                    // If no text follows the `#`, treat it as a Paragraph
-                    let header_text_token = Token {
-                        token_type: TokenType::Text,
-                        value: "#".repeat(header_level),
-                        line: header_line,
-                    };
-                    stream.back();
-                    stream.replace(header_text_token);
-                    return parse_paragraph(stream);
-                }
-            }
+    let header_start = LineSpan {
+        start: span_offset(header_line),
+        end: span_offset(header_line),
+        start_col: span_offset(header_column),
+        end_col: span_offset(header_column),
+        bytes: ByteSpan {
+            start: span_offset(header_byte),
+            end: span_offset(header_byte),
+        },
+    };
+    let whitespace = next.as_ref().map(token_span).unwrap_or_default();
+
+    // Skip the markers and the whitespace, and parse the rest of the line as the header text
+    stream.index += header_level + 1;
+    let nodes = parse_line(stream);
+    let header_end = match nodes.last() {
+        Some(node) => node.position().clone(),
+        None => whitespace,
+    };
+
+    Node::Header(Header {
+        level: header_level,
+        nodes,
+        position: join_spans(&header_start, &header_end),
+    })
+}
+
+/// Parses a paragraph, which becomes a header if the next line underlines it.
+///
+/// A line of only `=` makes a level 1 header and a line of only `-` a level 2 header.
+/// Consecutive paragraph lines directly above the underline all belong to the header,
+/// and are joined with an `Eol` between them.
+fn parse_setext_header(stream: &mut TokenStream) -> Node {
+    let paragraph = parse_hard_broken_paragraph(stream);
+    if !is_setext_text(&paragraph) {
+        return paragraph;
+    }
+
+    let rewind = stream.index;
+    let diagnostics_len = stream.diagnostics.len();
+    // The lines are kept with the line breaks between them
+    let mut lines = vec![paragraph];
+    loop {
+        if let Some((level, end)) = parse_setext_underline(stream) {
+            return setext_header(lines, level, end);
+        }
+        if stream.index < stream.setext_checked_until || !is_next_paragraph_line(stream) {
+            break;
+        }
+        let eol = match stream.get(stream.index - 1) {
+            Some(token) if token.token_type == TokenType::Eol => Node::Eol(Eol {
+                position: token_span(token),
+            }),
+            _ => break,
+        };
+        let line = parse_hard_broken_paragraph(stream);
+        if !is_setext_text(&line) {
+            break;
+        }
+        lines.push(eol);
+        lines.push(line);
+    }
+
+    // No underline follows, so the lines after the first are parsed again as their own blocks
+    if stream.index > rewind {
+        stream.setext_checked_until = stream.index;
+        stream.diagnostics.truncate(diagnostics_len);
+    }
+    stream.index = rewind;
+    lines.swap_remove(0)
+}
+
+/// Returns whether the node is a paragraph with content, which an underline can turn into a header.
+fn is_setext_text(node: &Node) -> bool {
+    matches!(node, Node::Paragraph(paragraph) if !paragraph.nodes.is_empty())
+}
+
+/// Returns whether the current line would be parsed as a paragraph.
+fn is_next_paragraph_line(stream: &TokenStream) -> bool {
+    let Some(token) = stream.peek() else {
+        return false;
+    };
+    let is_block_marker = matches!(
+        token.token_type,
+        TokenType::Header
+            | TokenType::UnorderedList
+            | TokenType::OrderedList
+            | TokenType::BlockQuote
+            | TokenType::CodeBlock
+            | TokenType::AlertStart
+            | TokenType::AlertEnd
+            | TokenType::Eol
+    );
+    !is_block_marker
+        && !is_blank_line(stream)
+        && stream.is_next_list().is_none()
+        && link_definition_at(stream.tokens, stream.index).is_none()
+        && footnote_definition_at(stream.tokens, stream.index).is_none()
+}
+
+/// Consumes a setext underline line and returns its header level and span.
+///
+/// Returns `None` without consuming anything if the current line is not an underline.
+fn parse_setext_underline(stream: &mut TokenStream) -> Option<(usize, LineSpan)> {
+    // The underline is a single text token, optionally followed by whitespace
+    let underline = stream.peek()?;
+    let (level, marker) = match underline.value.chars().next() {
+        Some('=') => (1, '='),
+        Some('-') => (2, '-'),
+        _ => return None,
+    };
+    if underline.token_type != TokenType::Text || !underline.value.chars().all(|c| c == marker) {
+        return None;
+    }
+    let end = token_span(underline);
+    let line_end = stream.tokens[stream.index + 1..]
+        .iter()
+        .position(|token| token.token_type != TokenType::Whitespace)
+        .map(|ix| stream.index + 1 + ix);
+    if line_end.is_some_and(|ix| stream.tokens[ix].token_type != TokenType::Eol) {
+        return None;
+    }
+
+    // Consume the underline up to and including its line break
+    stream.index = line_end.map_or(stream.tokens.len(), |ix| ix + 1);
+    Some((level, end))
+}
+
+/// Joins the paragraph lines above an underline, and the line breaks between them, into one header.
+fn setext_header(lines: Vec<Node>, level: usize, end: LineSpan) -> Node {
+    let start = lines[0].position().clone();
+    let mut nodes: Vec<Node> = vec![];
+    for line in lines {
+        match line {
+            Node::Paragraph(paragraph) => nodes.extend(paragraph.nodes),
+            eol => nodes.push(eol),
         }
     }
 
     Node::Header(Header {
-        level: header_level,
+        level,
         nodes,
-        position: LineSpan {
-            start: header_position,
-            end: header_position,
-        },
+        position: join_spans(&start, &end),
     })
 }
 
@@ -465,69 +1637,197 @@ fn parse_header(stream: &mut TokenStream) -> Node {
 fn parse_paragraph(stream: &mut TokenStream) -> Node {
     let nodes: Vec<Node> = parse_line(stream);
     let position = if !nodes.is_empty() {
-        let start = nodes.first().unwrap().position().start;
-        let end = nodes.last().unwrap().position().end;
-        LineSpan { start, end }
+        let start = nodes.first().unwrap().position();
+        let end = nodes.last().unwrap().position();
+        join_spans(start, end)
     } else {
-        // If there are no tokens to parse, refer to the previous token and use its line number
-        if let Some(prev_token) = stream.get(stream.index - 1) {
+        // If there are no tokens to parse, refer to the previous token and use its position
+        if let Some(prev_token) = stream.get(stream.index - 1).or(stream.tokens.last()) {
             return Node::Paragraph(Paragraph {
                 nodes,
                 position: LineSpan {
-                    start: prev_token.line,
-                    end: prev_token.line,
+                    start: span_offset(prev_token.line),
+                    end: span_offset(prev_token.line),
+                    start_col: span_offset(prev_token.column),
+                    end_col: span_offset(prev_token.column),
+                    bytes: ByteSpan {
+                        start: span_offset(prev_token.start_byte),
+                        end: span_offset(prev_token.start_byte),
+                    },
                 },
             });
         }
-        LineSpan { start: 0, end: 0 }
+        LineSpan::default()
     };
     Node::Paragraph(Paragraph { nodes, position })
 }
 
+/// Parses a paragraph that goes on to the next line after each hard line break.
+///
+/// A line ending in two or more spaces or a backslash continues with the next line
+/// when that line would be a paragraph too, with a `HardBreak` in place of the marker.
+fn parse_hard_broken_paragraph(stream: &mut TokenStream) -> Node {
+    let mut node = parse_paragraph(stream);
+    let Node::Paragraph(paragraph) = &mut node else {
+        return node;
+    };
+    // Only a line that ended in a line break can go on
+    while stream
+        .index
+        .checked_sub(1)
+        .and_then(|prev| stream.get(prev))
+        .is_some_and(|token| token.token_type == TokenType::Eol)
+        && is_next_paragraph_line(stream)
+        && !is_setext_underline(stream)
+    {
+        let Some(hard_break) = take_hard_break(&mut paragraph.nodes) else {
+            break;
+        };
+        paragraph.nodes.push(hard_break);
+        paragraph.nodes.extend(parse_line(stream));
+        if let (Some(first), Some(last)) = (paragraph.nodes.first(), paragraph.nodes.last()) {
+            paragraph.position = join_spans(first.position(), last.position());
+        }
+    }
+    node
+}
+
+/// Removes the hard break marker at the end of the line and returns a `HardBreak` in its place.
+///
+/// The marker is a whitespace run of two or more columns, or a single backslash
+/// at the end of the last text. Returns `None` if the line has no marker.
+fn take_hard_break(nodes: &mut Vec<Node>) -> Option<Node> {
+    let position = match nodes.last_mut()? {
+        Node::Whitespace(whitespace)
+            if whitespace.position.end_col - whitespace.position.start_col >= 2 =>
+        {
+            let position = whitespace.position.clone();
+            nodes.pop();
+            position
+        }
+        Node::Text(text) if text.value.ends_with('\\') && !text.value.ends_with("\\\\") => {
+            text.value.pop();
+            let mut position = text.position.clone();
+            position.start = position.end;
+            position.start_col = position.end_col - 1;
+            position.bytes.start = position.bytes.end - 1;
+            text.position.end_col -= 1;
+            text.position.bytes.end -= 1;
+            if text.value.is_empty() {
+                nodes.pop();
+            }
+            position
+        }
+        _ => return None,
+    };
+    Some(Node::HardBreak(HardBreak { position }))
+}
+
+/// Returns whether the current line is a setext underline, without consuming it.
+fn is_setext_underline(stream: &mut TokenStream) -> bool {
+    let index = stream.index;
+    let is_underline = parse_setext_underline(stream).is_some();
+    stream.index = index;
+    is_underline
+}
+
+/// Parses italic text. Called right after the opening marker has been consumed.
+///
+/// A marker followed by whitespace cannot open italic text, and a marker after
+/// whitespace cannot close it, so the `*` in `a * b` are kept as literal text.
 fn parse_italic(stream: &mut TokenStream) -> Vec<Node> {
     let mut nodes: Vec<Node> = vec![];
     let mut is_closed = false;
-    let mut start: usize = 0;
-    let mut end: usize = 0;
+    // The opening marker has already been consumed by `parse_line`
+    let (start, marker) = match stream.get(stream.index - 1) {
+        Some(token) => (token_span(token), token.value.clone()),
+        None => (LineSpan::default(), "*".to_string()),
+    };
+    let mut end = start.clone();
+
+    let can_open = stream
+        .peek()
+        .is_some_and(|token| !matches!(token.token_type, TokenType::Whitespace | TokenType::Eol));
+    if !can_open {
+        return vec![Node::Text(Text {
+            value: marker,
+            position: start,
+        })];
+    }
 
     while let Some(token) = stream.peek() {
         match token.token_type {
-            TokenType::Italic => {
+            // Only the marker that opened the italic text closes it, so `*a_` stays literal
+            TokenType::Italic
+                if token.value == marker
+                    && stream
+                        .get(stream.index - 1)
+                        .is_some_and(|prev| prev.token_type != TokenType::Whitespace) =>
+            {
                 is_closed = true;
             }
-            TokenType::Whitespace => {
-                if is_closed {
-                    break;
-                } else {
-                    nodes.push(parse_token(token));
+            // In a closing `***`, the italic closes on the first `*` and leaves the `**`
+            // to the bold around it, so the run is split into an italic and a bold marker
+            TokenType::Bold
+                if !is_closed
+                    && stream.get(stream.index + 1).is_some_and(|next| {
+                        next.token_type == TokenType::Italic && next.value == marker
+                    })
+                    && stream
+                        .get(stream.index - 1)
+                        .is_some_and(|prev| prev.token_type != TokenType::Whitespace) =>
+            {
+                split_closing_run(stream);
+                continue;
+            }
+            // A marker right after the closing one belongs to the emphasis around this one
+            TokenType::Bold if is_closed => {
+                break;
+            }
+            // Bold can be nested inside italic when it is closed on the same line
+            TokenType::Bold if !is_closed && has_closing_marker(stream) => {
+                stream.next();
+                let bold = parse_bold(stream);
+                if let Some(last) = bold.last() {
+                    end = last.position().clone();
                 }
+                nodes.extend(bold);
+                continue;
+            }
+            // Links can be nested inside emphasis
+            TokenType::SquareBracketOpen if !is_closed => {
+                stream.next();
+                let link = parse_link(stream);
+                end = link.position().clone();
+                nodes.push(link);
+                continue;
+            }
+            TokenType::Whitespace if is_closed => {
+                break;
             }
             TokenType::Eol => {
                 break;
             }
             _ => {
-                nodes.push(parse_token(token));
+                nodes.push(parse_token(token, stream.options.tab_width));
             }
         }
-        if start == 0 {
-            start = token.line;
-        }
-        end = end.max(token.line);
+        end = token_span(token);
         stream.next();
     }
 
     if !is_closed {
-        let mut italic_token_line = 0;
-        if let Some(prev_token) = stream.get(stream.index - 1) {
-            italic_token_line = prev_token.line;
+        stream.warn(
+            format!("unclosed emphasis marker `{}`", marker),
+            start.start as usize,
+        );
+        if stream.options.strict_emphasis {
+            return nodes;
         }
 
         let italic_text_token = Node::Text(Text {
-            value: "*".to_string(),
-            position: LineSpan {
-                start: italic_token_line,
-                end: italic_token_line,
-            },
+            value: marker,
+            position: start,
         });
         let mut new_vec = vec![italic_text_token];
         new_vec.extend(nodes);
@@ -536,54 +1836,74 @@ fn parse_italic(stream: &mut TokenStream) -> Vec<Node> {
 
     vec![Node::Italic(Italic {
         nodes,
-        position: LineSpan { start, end },
+        position: join_spans(&start, &end),
     })]
 }
 
 fn parse_bold(stream: &mut TokenStream) -> Vec<Node> {
     let mut nodes: Vec<Node> = vec![];
     let mut is_closed = false;
-    let mut start: usize = 0;
-    let mut end: usize = 0;
+    // The opening marker has already been consumed by `parse_line`
+    let (start, marker) = match stream.get(stream.index - 1) {
+        Some(token) => (token_span(token), token.value.clone()),
+        None => (LineSpan::default(), "**".to_string()),
+    };
+    let mut end = start.clone();
 
     while let Some(token) = stream.peek() {
         match token.token_type {
-            TokenType::Bold => {
+            // Only the marker that opened the bold text closes it, so `__a**` stays literal
+            TokenType::Bold if token.value == marker => {
                 is_closed = true;
             }
-            TokenType::Whitespace => {
-                if is_closed {
-                    break;
-                } else {
-                    nodes.push(parse_token(token));
+            // A marker right after the closing one belongs to the emphasis around this one
+            TokenType::Italic if is_closed => {
+                break;
+            }
+            // Italic can be nested inside bold when it is closed on the same line
+            TokenType::Italic if !is_closed && has_closing_marker(stream) => {
+                stream.next();
+                let italic = parse_italic(stream);
+                if let Some(last) = italic.last() {
+                    end = last.position().clone();
                 }
+                nodes.extend(italic);
+                continue;
+            }
+            // Links can be nested inside emphasis
+            TokenType::SquareBracketOpen if !is_closed => {
+                stream.next();
+                let link = parse_link(stream);
+                end = link.position().clone();
+                nodes.push(link);
+                continue;
+            }
+            TokenType::Whitespace if is_closed => {
+                break;
             }
             TokenType::Eol => {
                 break;
             }
             _ => {
-                nodes.push(parse_token(token));
+                nodes.push(parse_token(token, stream.options.tab_width));
             }
         }
-        if start == 0 {
-            start = token.line;
-        }
-        end = end.max(token.line);
+        end = token_span(token);
         stream.next();
     }
 
     if !is_closed {
-        let mut bold_token_line = 0;
-        if let Some(prev_token) = stream.get(stream.index - 1) {
-            bold_token_line = prev_token.line;
+        stream.warn(
+            format!("unclosed emphasis marker `{}`", marker),
+            start.start as usize,
+        );
+        if stream.options.strict_emphasis {
+            return nodes;
         }
 
         let bold_text_token = Node::Text(Text {
-            value: "**".to_string(),
-            position: LineSpan {
-                start: bold_token_line,
-                end: bold_token_line,
-            },
+            value: marker,
+            position: start,
         });
         let mut new_vec = vec![bold_text_token];
         new_vec.extend(nodes);
@@ -592,66 +1912,588 @@ fn parse_bold(stream: &mut TokenStream) -> Vec<Node> {
 
     vec![Node::Bold(Bold {
         nodes,
-        position: LineSpan { start, end },
+        position: join_spans(&start, &end),
     })]
 }
 
-fn parse_token(token: &Token) -> Node {
-    match token.token_type {
-        TokenType::Whitespace => Node::Whitespace(Whitespace {
-            position: LineSpan {
-                start: token.line,
-                end: token.line,
-            },
-        }),
-        _ => Node::Text(Text {
-            value: token.value.to_string(),
-            position: LineSpan {
-                start: token.line,
-                end: token.line,
-            },
-        }),
-    }
+/// Swaps the bold marker at the current position with the italic marker after it.
+///
+/// The markers keep their values, so `**` and `*` become `*` and `**` over the same bytes.
+fn split_closing_run(stream: &mut TokenStream) {
+    let bold = stream.tokens[stream.index].clone();
+    let italic = stream.tokens[stream.index + 1].clone();
+    let italic_len = italic.end_byte - italic.start_byte;
+    stream.tokens[stream.index] = Token {
+        column: bold.column,
+        start_byte: bold.start_byte,
+        end_byte: bold.start_byte + italic_len,
+        ..italic
+    };
+    stream.tokens[stream.index + 1] = Token {
+        column: bold.column + italic_len,
+        start_byte: bold.start_byte + italic_len,
+        end_byte: bold.end_byte + italic_len,
+        ..bold
+    };
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::tree::{
-        Bold, Eol, Italic, LineSpan, Node, Paragraph, Text, UnorderedList, Whitespace,
+/// Checks whether the same marker as the current one follows it on the same line.
+fn has_closing_marker(stream: &TokenStream) -> bool {
+    let marker = &stream.tokens[stream.index];
+    stream.tokens[stream.index + 1..]
+        .iter()
+        .take_while(|token| token.token_type != TokenType::Eol)
+        .any(|token| token.token_type == marker.token_type && token.value == marker.value)
+}
+
+/// Parses text struck through with `~~`. Called right after the opening marker has been consumed.
+///
+/// An unclosed marker is kept as a literal `~~`, like an unclosed `**`.
+fn parse_strikethrough(stream: &mut TokenStream) -> Vec<Node> {
+    let mut nodes: Vec<Node> = vec![];
+    let mut is_closed = false;
+    let start = match stream.get(stream.index - 1) {
+        Some(token) => token_span(token),
+        None => LineSpan::default(),
     };
-    use pretty_assertions::assert_eq;
+    let mut end = start.clone();
 
-    mod header_tests {
-        use super::*;
-        use pretty_assertions::assert_eq;
+    while let Some(token) = stream.peek() {
+        match token.token_type {
+            TokenType::Strikethrough => {
+                is_closed = true;
+            }
+            // Links can be nested inside strikethrough
+            TokenType::SquareBracketOpen if !is_closed => {
+                stream.next();
+                let link = parse_link(stream);
+                end = link.position().clone();
+                nodes.push(link);
+                continue;
+            }
+            TokenType::Whitespace if is_closed => {
+                break;
+            }
+            TokenType::Eol => {
+                break;
+            }
+            _ => {
+                nodes.push(parse_token(token, stream.options.tab_width));
+            }
+        }
+        end = token_span(token);
+        stream.next();
+    }
 
-        #[test]
-        fn test_header_marker() {
-            let input = "# Header text";
-            let nodes = build_tree(input);
+    if !is_closed {
+        stream.warn(
+            "unclosed emphasis marker `~~`".to_string(),
+            start.start as usize,
+        );
+        if stream.options.strict_emphasis {
+            return nodes;
+        }
 
-            assert_eq!(
-                nodes,
-                vec![Node::Header(Header {
-                    level: 1,
-                    nodes: vec![Node::Paragraph(Paragraph {
-                        nodes: vec![
-                            Node::Text(Text {
-                                value: "Header".to_string(),
-                                position: LineSpan { start: 1, end: 1 }
-                            }),
-                            Node::Whitespace(Whitespace {
-                                position: LineSpan { start: 1, end: 1 }
-                            }),
-                            Node::Text(Text {
-                                value: "text".to_string(),
-                                position: LineSpan { start: 1, end: 1 }
-                            }),
-                        ],
-                        position: LineSpan { start: 1, end: 1 }
+        let strikethrough_text_token = Node::Text(Text {
+            value: "~~".to_string(),
+            position: start,
+        });
+        let mut new_vec = vec![strikethrough_text_token];
+        new_vec.extend(nodes);
+        return new_vec;
+    }
+
+    vec![Node::Strikethrough(Strikethrough {
+        nodes,
+        position: join_spans(&start, &end),
+    })]
+}
+
+/// Parses a code span. Called right after the opening backtick has been consumed.
+///
+/// The tokens up to the closing backtick are captured verbatim, so `*` and `**`
+/// inside a code span are not treated as emphasis.
+/// As in CommonMark, one space is stripped from each side when the content both
+/// starts and ends with a space and is not only spaces.
+fn parse_inline_code(stream: &mut TokenStream) -> Node {
+    let mut value = String::new();
+    let start = match stream.get(stream.index - 1) {
+        Some(token) => token_span(token),
+        None => LineSpan::default(),
+    };
+
+    // Look ahead for the closing backtick before consuming anything
+    let mut ix = stream.index;
+    while let Some(token) = stream.get(ix) {
+        match token.token_type {
+            TokenType::InlineCode => {
+                let end = token_span(token);
+                stream.index = ix + 1;
+                if value.len() >= 2
+                    && value.starts_with(' ')
+                    && value.ends_with(' ')
+                    && !value.chars().all(|c| c == ' ')
+                {
+                    value = value[1..value.len() - 1].to_string();
+                }
+                return Node::InlineCode(InlineCode {
+                    value,
+                    position: join_spans(&start, &end),
+                });
+            }
+            TokenType::Eol => break,
+            _ => value.push_str(&token.value),
+        }
+        ix += 1;
+    }
+
+    // If the code span is not closed, the backtick is kept as a literal text
+    Node::Text(Text {
+        value: "`".to_string(),
+        position: start,
+    })
+}
+
+fn parse_link(stream: &mut TokenStream) -> Node {
+    let start = match stream.get(stream.index - 1) {
+        Some(token) => token_span(token),
+        None => LineSpan::default(),
+    };
+
+    if let Some(footnote_ref) = parse_footnote_reference(stream, &start) {
+        return footnote_ref;
+    }
+    let parts = parse_link_parts(stream, stream.index)
+        .or_else(|| parse_reference_parts(stream, stream.index));
+    if let Some((text, url, title, end)) = parts {
+        return Node::Link(Link {
+            text,
+            url,
+            title,
+            position: join_spans(&start, &end),
+        });
+    }
+
+    // If the link is malformed, the bracket is kept as a literal text
+    Node::Text(Text {
+        value: "[".to_string(),
+        position: start,
+    })
+}
+
+fn parse_image(stream: &mut TokenStream) -> Node {
+    let start = match stream.get(stream.index - 1) {
+        Some(token) => token_span(token),
+        None => LineSpan::default(),
+    };
+
+    // An image is a `!` directly followed by a link
+    if stream
+        .peek()
+        .is_some_and(|token| token.token_type == TokenType::SquareBracketOpen)
+    {
+        if let Some((text, src, title, end)) = parse_link_parts(stream, stream.index + 1) {
+            return Node::Image(Image {
+                alt: plain_text(&text),
+                src,
+                title,
+                position: join_spans(&start, &end),
+            });
+        }
+    }
+
+    Node::Text(Text {
+        value: "!".to_string(),
+        position: start,
+    })
+}
+
+/// Parses the `text](url "title")` part of a link whose text starts at `text_start`.
+///
+/// Returns the parsed text, the url, the title and the span of the closing `)`,
+/// and moves the stream past the link. Returns `None` without consuming anything
+/// if the link is malformed.
+fn parse_link_parts(
+    stream: &mut TokenStream,
+    text_start: usize,
+) -> Option<(Vec<Node>, String, Option<String>, LineSpan)> {
+    // Look ahead for `](...)` on the same line before consuming anything
+    let (text_end, destination_end) = find_link_end(stream, text_start)?;
+    let destination = join_values(&stream.tokens[text_end + 2..destination_end]);
+    let (url, title) = split_link_destination(&destination)?;
+
+    let text = parse_inline_range(stream, text_start, text_end);
+    let end = token_span(&stream.tokens[destination_end]);
+    stream.index = destination_end + 1;
+
+    Some((text, url, title, end))
+}
+
+/// Parses the `text][label]` part of a reference link whose text starts at `text_start`.
+///
+/// A collapsed reference (`[label][]`) uses the link text as its label.
+/// Returns `None` without consuming anything if the label is not defined in the document.
+fn parse_reference_parts(
+    stream: &mut TokenStream,
+    text_start: usize,
+) -> Option<(Vec<Node>, String, Option<String>, LineSpan)> {
+    let text_end = find_link_text_end(stream, text_start)?;
+    if stream.get(text_end + 1)?.token_type != TokenType::SquareBracketOpen {
+        return None;
+    }
+    let mut label_end = text_end + 2;
+    loop {
+        match stream.get(label_end)?.token_type {
+            TokenType::SquareBracketClose => break,
+            TokenType::SquareBracketOpen | TokenType::Eol => return None,
+            _ => label_end += 1,
+        }
+    }
+    let label = match label_end == text_end + 2 {
+        true => join_values(&stream.tokens[text_start..text_end]),
+        false => join_values(&stream.tokens[text_end + 2..label_end]),
+    };
+    let (url, title) = stream
+        .link_definitions
+        .get(&normalize_label(&label))?
+        .clone();
+
+    let text = parse_inline_range(stream, text_start, text_end);
+    let end = token_span(&stream.tokens[label_end]);
+    stream.index = label_end + 1;
+
+    Some((text, url, title, end))
+}
+
+/// Parses the tokens from `start` to `end` into inline nodes, like the text of a link.
+fn parse_inline_range(stream: &mut TokenStream, start: usize, end: usize) -> Vec<Node> {
+    // The range is parsed on its own so that emphasis cannot run past its end, like a `]`
+    let mut text_tokens = stream.tokens[start..end].to_vec();
+    // It takes over the parent's options and maps while it runs, instead of copying them
+    let mut text_stream = TokenStream {
+        tokens: &mut text_tokens,
+        index: 0,
+        options: std::mem::take(&mut stream.options),
+        diagnostics: vec![],
+        link_definitions: std::mem::take(&mut stream.link_definitions),
+        footnote_ids: std::mem::take(&mut stream.footnote_ids),
+        setext_checked_until: 0,
+    };
+    let text = parse_line(&mut text_stream);
+    stream.options = text_stream.options;
+    stream.link_definitions = text_stream.link_definitions;
+    stream.footnote_ids = text_stream.footnote_ids;
+    stream.diagnostics.append(&mut text_stream.diagnostics);
+    text
+}
+
+/// Finds the index of the `]` closing the link text that starts at `text_start`.
+fn find_link_text_end(stream: &TokenStream, text_start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut ix = text_start;
+    loop {
+        match stream.get(ix)?.token_type {
+            TokenType::SquareBracketOpen => depth += 1,
+            TokenType::SquareBracketClose if depth == 0 => return Some(ix),
+            TokenType::SquareBracketClose => depth -= 1,
+            TokenType::Eol => return None,
+            _ => {}
+        }
+        ix += 1;
+    }
+}
+
+/// Finds the indexes of the `]` closing the link text and the `)` closing its destination.
+fn find_link_end(stream: &TokenStream, text_start: usize) -> Option<(usize, usize)> {
+    let text_end = find_link_text_end(stream, text_start)?;
+    if stream.get(text_end + 1)?.token_type != TokenType::ParenthesisOpen {
+        return None;
+    }
+    let mut ix = text_end + 2;
+    loop {
+        match stream.get(ix)?.token_type {
+            TokenType::ParenthesisClose => return Some((text_end, ix)),
+            TokenType::Eol => return None,
+            _ => ix += 1,
+        }
+    }
+}
+
+/// Splits a link destination into the url and an optional quoted title.
+fn split_link_destination(destination: &str) -> Option<(String, Option<String>)> {
+    let destination = destination.trim();
+    match destination.split_once(' ') {
+        Some((url, title)) => {
+            let title = title.trim().strip_prefix('"')?.strip_suffix('"')?;
+            Some((url.to_string(), Some(title.to_string())))
+        }
+        None => Some((destination.to_string(), None)),
+    }
+}
+
+/// Collects the link definitions of the document.
+///
+/// When a label is defined more than once, the first definition wins.
+fn collect_link_definitions(tokens: &[Token]) -> LinkDefinitions {
+    let mut definitions = LinkDefinitions::new();
+    for ix in 0..tokens.len() {
+        if let Some((label, url, title, _)) = link_definition_at(tokens, ix) {
+            definitions.entry(label).or_insert((url, title));
+        }
+    }
+    definitions
+}
+
+/// Parses a `[label]: url "title"` line starting at `ix`.
+///
+/// Returns the normalized label, the url, the title and the index of the token after
+/// the line, or `None` if the tokens at `ix` do not start a link definition line.
+fn link_definition_at(
+    tokens: &[Token],
+    ix: usize,
+) -> Option<(String, String, Option<String>, usize)> {
+    if tokens.get(ix)?.token_type != TokenType::SquareBracketOpen {
+        return None;
+    }
+    if ix > 0 && tokens[ix - 1].token_type != TokenType::Eol {
+        return None;
+    }
+    // `[^id]:` defines a footnote instead
+    if tokens.get(ix + 1)?.token_type == TokenType::Annotation {
+        return None;
+    }
+    let label_end = ix
+        + 1
+        + tokens[ix + 1..].iter().position(|token| {
+            matches!(
+                token.token_type,
+                TokenType::SquareBracketOpen | TokenType::SquareBracketClose | TokenType::Eol
+            )
+        })?;
+    if tokens[label_end].token_type != TokenType::SquareBracketClose
+        || tokens.get(label_end + 1)?.token_type != TokenType::Colon
+    {
+        return None;
+    }
+    let label = normalize_label(&join_values(&tokens[ix + 1..label_end]));
+
+    let line_end = tokens[label_end..]
+        .iter()
+        .position(|token| token.token_type == TokenType::Eol)
+        .map_or(tokens.len(), |offset| label_end + offset);
+    let (url, title) = split_link_destination(&join_values(&tokens[label_end + 2..line_end]))?;
+    if label.is_empty() || url.is_empty() {
+        return None;
+    }
+
+    // The line break after the definition belongs to it
+    Some((label, url, title, (line_end + 1).min(tokens.len())))
+}
+
+/// Parses a `[^id]` footnote reference. Called right after the `[` has been consumed.
+///
+/// Returns `None` without consuming anything if no footnote with the id is defined.
+fn parse_footnote_reference(stream: &mut TokenStream, start: &LineSpan) -> Option<Node> {
+    let (id, id_end) = footnote_id_at(stream.tokens, stream.index)?;
+    if !stream.footnote_ids.contains(&normalize_label(&id)) {
+        return None;
+    }
+    let end = token_span(&stream.tokens[id_end]);
+    stream.index = id_end + 1;
+    Some(Node::FootnoteRef(FootnoteRef {
+        id,
+        position: join_spans(start, &end),
+    }))
+}
+
+/// Parses a `[^id]: text` footnote definition line, with the text parsed as inline nodes.
+///
+/// Returns `None` without consuming anything if the current line is not a definition.
+fn parse_footnote_definition(stream: &mut TokenStream) -> Option<Node> {
+    let (id, content_start) = footnote_definition_at(stream.tokens, stream.index)?;
+    let start = token_span(&stream.tokens[stream.index]);
+    let marker_end = token_span(&stream.tokens[content_start - 1]);
+    stream.index = content_start;
+    skip_indent(stream);
+    let nodes = parse_line(stream);
+    let end = nodes
+        .last()
+        .map_or(marker_end, |node| node.position().clone());
+    Some(Node::FootnoteDef(FootnoteDef {
+        id,
+        nodes,
+        position: join_spans(&start, &end),
+    }))
+}
+
+/// Collects the normalized ids of the footnote definitions of the document.
+fn collect_footnote_ids(tokens: &[Token]) -> BTreeSet<String> {
+    (0..tokens.len())
+        .filter_map(|ix| footnote_definition_at(tokens, ix))
+        .map(|(id, _)| normalize_label(&id))
+        .collect()
+}
+
+/// Checks for a `[^id]:` marker at the start of the line at `ix`.
+///
+/// Returns the id and the index of the token after the `:`.
+fn footnote_definition_at(tokens: &[Token], ix: usize) -> Option<(String, usize)> {
+    if tokens.get(ix)?.token_type != TokenType::SquareBracketOpen
+        || (ix > 0 && tokens[ix - 1].token_type != TokenType::Eol)
+    {
+        return None;
+    }
+    let (id, id_end) = footnote_id_at(tokens, ix + 1)?;
+    if tokens.get(id_end + 1)?.token_type != TokenType::Colon {
+        return None;
+    }
+    Some((id, id_end + 2))
+}
+
+/// Reads the `^id]` after the `[` of a footnote, starting from the `^` at `ix`.
+///
+/// The id is one run of text without whitespace. Returns it and the index of the `]`.
+fn footnote_id_at(tokens: &[Token], ix: usize) -> Option<(String, usize)> {
+    if tokens.get(ix)?.token_type != TokenType::Annotation {
+        return None;
+    }
+    let id_end = ix
+        + 1
+        + tokens[ix + 1..]
+            .iter()
+            .position(|token| token.token_type != TokenType::Text)?;
+    if id_end == ix + 1 || tokens[id_end].token_type != TokenType::SquareBracketClose {
+        return None;
+    }
+    Some((join_values(&tokens[ix + 1..id_end]), id_end))
+}
+
+/// Normalizes a link label, so that `[Foo  Bar]` and `[foo bar]` refer to the same definition.
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Returns the source text of the given tokens.
+fn join_values(tokens: &[Token]) -> String {
+    tokens.iter().map(|token| token.value.as_str()).collect()
+}
+
+fn parse_token(token: &Token, tab_width: usize) -> Node {
+    match token.token_type {
+        TokenType::Whitespace => Node::Whitespace(Whitespace {
+            position: whitespace_span(token, tab_width),
+        }),
+        _ => Node::Text(Text {
+            value: token.value.to_string(),
+            position: token_span(token),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::clear_positions;
+    use crate::tree::{
+        Bold, CodeBlock, Eol, FootnoteDef, FootnoteRef, FrontMatter, HardBreak, Image, InlineCode,
+        Italic, LineSpan, Link, Node, OrderedList, Paragraph, Strikethrough, Text, UnorderedList,
+        Whitespace,
+    };
+
+    mod header_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_header_with_bold_text() {
+            let input = "# **bold**";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Header(Header {
+                    level: 1,
+                    nodes: vec![Node::Bold(Bold {
+                        nodes: vec![Node::Text(Text {
+                            value: "bold".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 5,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 4, end: 8 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 3,
+                            end_col: 11,
+                            bytes: ByteSpan { start: 2, end: 10 }
+                        }
                     })],
-                    position: LineSpan { start: 1, end: 1 }
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 11,
+                        bytes: ByteSpan { start: 0, end: 10 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_header_marker() {
+            let input = "# Header text";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Header(Header {
+                    level: 1,
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "Header".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 2, end: 8 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 9,
+                                end_col: 10,
+                                bytes: ByteSpan { start: 8, end: 9 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "text".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 10,
+                                end_col: 14,
+                                bytes: ByteSpan { start: 9, end: 13 }
+                            }
+                        }),
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 14,
+                        bytes: ByteSpan { start: 0, end: 13 }
+                    }
                 })]
             )
         }
@@ -666,23 +2508,152 @@ mod tests {
                 vec![
                     Node::Header(Header {
                         level: 3,
-                        nodes: vec![Node::Paragraph(Paragraph {
-                            nodes: vec![],
-                            position: LineSpan { start: 1, end: 1 }
-                        })],
-                        position: LineSpan { start: 1, end: 1 }
+                        nodes: vec![],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 5,
+                            bytes: ByteSpan { start: 0, end: 4 }
+                        }
                     }),
                     Node::Paragraph(Paragraph {
                         nodes: vec![Node::Text(Text {
                             value: "text".to_string(),
-                            position: LineSpan { start: 2, end: 2 }
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 1,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 5, end: 9 }
+                            }
                         }),],
-                        position: LineSpan { start: 2, end: 2 }
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 5,
+                            bytes: ByteSpan { start: 5, end: 9 }
+                        }
                     })
                 ]
             )
         }
 
+        #[test]
+        fn test_header_marker_only() {
+            for input in ["#", "##"] {
+                let nodes = build_tree(input);
+
+                assert_eq!(
+                    nodes,
+                    vec![Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: input.to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: input.len() as u32 + 1,
+                                bytes: ByteSpan {
+                                    start: 0,
+                                    end: input.len() as u32
+                                }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: input.len() as u32 + 1,
+                            bytes: ByteSpan {
+                                start: 0,
+                                end: input.len() as u32
+                            }
+                        }
+                    })]
+                )
+            }
+        }
+
+        #[test]
+        fn test_header_marker_with_space_only() {
+            let input = "# ";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Header(Header {
+                    level: 1,
+                    nodes: vec![],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 3,
+                        bytes: ByteSpan { start: 0, end: 2 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_header_marker_without_space() {
+            for input in ["#Header", "##Header"] {
+                let nodes = build_tree(input);
+
+                assert_eq!(
+                    nodes,
+                    vec![Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: input.to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: input.len() as u32 + 1,
+                                bytes: ByteSpan {
+                                    start: 0,
+                                    end: input.len() as u32
+                                }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: input.len() as u32 + 1,
+                            bytes: ByteSpan {
+                                start: 0,
+                                end: input.len() as u32
+                            }
+                        }
+                    })]
+                )
+            }
+        }
+
+        #[test]
+        fn test_header_marker_with_space_and_eol() {
+            let input = "## \n";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Header(Header {
+                    level: 2,
+                    nodes: vec![],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 4,
+                        bytes: ByteSpan { start: 0, end: 3 }
+                    }
+                })]
+            )
+        }
+
         #[test]
         fn test_too_long_header_marker() {
             let input = "####### Header text\n";
@@ -694,24 +2665,60 @@ mod tests {
                     nodes: vec![
                         Node::Text(Text {
                             value: "#######".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 0, end: 7 }
+                            }
                         }),
                         Node::Whitespace(Whitespace {
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 8,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 7, end: 8 }
+                            }
                         }),
                         Node::Text(Text {
                             value: "Header".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 9,
+                                end_col: 15,
+                                bytes: ByteSpan { start: 8, end: 14 }
+                            }
                         }),
                         Node::Whitespace(Whitespace {
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 15,
+                                end_col: 16,
+                                bytes: ByteSpan { start: 14, end: 15 }
+                            }
                         }),
                         Node::Text(Text {
                             value: "text".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 16,
+                                end_col: 20,
+                                bytes: ByteSpan { start: 15, end: 19 }
+                            }
                         }),
                     ],
-                    position: LineSpan { start: 1, end: 1 }
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 20,
+                        bytes: ByteSpan { start: 0, end: 19 }
+                    }
                 },)],
             )
         }
@@ -727,87 +2734,493 @@ mod tests {
                     nodes: vec![
                         Node::Text(Text {
                             value: "#Header".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 0, end: 7 }
+                            }
                         }),
                         Node::Whitespace(Whitespace {
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 8,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 7, end: 8 }
+                            }
                         }),
                         Node::Text(Text {
                             value: "text".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 9,
+                                end_col: 13,
+                                bytes: ByteSpan { start: 8, end: 12 }
+                            }
                         }),
                     ],
-                    position: LineSpan { start: 1, end: 1 }
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 13,
+                        bytes: ByteSpan { start: 0, end: 12 }
+                    }
                 },)],
             )
         }
-    }
-
-    mod unordered_list_tests {
-        use super::*;
-        use pretty_assertions::assert_eq;
 
         #[test]
-        fn test_unordered_list() {
-            let input = "- item 1\n- item 2\n- item 3\n";
+        fn test_consecutive_headers() {
+            let input = "# A\n## B\n### C";
             let nodes = build_tree(input);
 
             assert_eq!(
                 nodes,
                 vec![
-                    Node::UnorderedList(UnorderedList {
-                        level: 0,
-                        nodes: vec![
+                    Node::Header(Header {
+                        level: 1,
+                        nodes: vec![Node::Text(Text {
+                            value: "A".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 2, end: 3 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 4,
+                            bytes: ByteSpan { start: 0, end: 3 }
+                        }
+                    }),
+                    Node::Header(Header {
+                        level: 2,
+                        nodes: vec![Node::Text(Text {
+                            value: "B".to_string(),
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 4,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 7, end: 8 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 5,
+                            bytes: ByteSpan { start: 4, end: 8 }
+                        }
+                    }),
+                    Node::Header(Header {
+                        level: 3,
+                        nodes: vec![Node::Text(Text {
+                            value: "C".to_string(),
+                            position: LineSpan {
+                                start: 3,
+                                end: 3,
+                                start_col: 5,
+                                end_col: 6,
+                                bytes: ByteSpan { start: 13, end: 14 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 3,
+                            end: 3,
+                            start_col: 1,
+                            end_col: 6,
+                            bytes: ByteSpan { start: 9, end: 14 }
+                        }
+                    }),
+                ]
+            )
+        }
+    }
+
+    mod setext_header_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_setext_header_level_1() {
+            let input = "Title\n===";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Header(Header {
+                    level: 1,
+                    nodes: vec![Node::Text(Text {
+                        value: "Title".to_string(),
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 6,
+                            bytes: ByteSpan { start: 0, end: 5 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 2,
+                        start_col: 1,
+                        end_col: 4,
+                        bytes: ByteSpan { start: 0, end: 9 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_setext_header_level_2() {
+            let input = "Sub\n---";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Header(Header {
+                    level: 2,
+                    nodes: vec![Node::Text(Text {
+                        value: "Sub".to_string(),
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 4,
+                            bytes: ByteSpan { start: 0, end: 3 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 2,
+                        start_col: 1,
+                        end_col: 4,
+                        bytes: ByteSpan { start: 0, end: 7 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_underline_after_blank_line() {
+            let input = "text\n\n---";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: "text".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 0, end: 4 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 5,
+                            bytes: ByteSpan { start: 0, end: 4 }
+                        }
+                    }),
+                    Node::Eol(Eol {
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 2,
+                            bytes: ByteSpan { start: 5, end: 6 }
+                        }
+                    }),
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: "---".to_string(),
+                            position: LineSpan {
+                                start: 3,
+                                end: 3,
+                                start_col: 1,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 6, end: 9 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 3,
+                            end: 3,
+                            start_col: 1,
+                            end_col: 4,
+                            bytes: ByteSpan { start: 6, end: 9 }
+                        }
+                    }),
+                ]
+            )
+        }
+
+        #[test]
+        fn test_setext_header_multiple_lines() {
+            let input = "one\ntwo\n===";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Header(Header {
+                    level: 1,
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "one".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 0, end: 3 }
+                            }
+                        }),
+                        Node::Eol(Eol {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 4,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 3, end: 4 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "two".to_string(),
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 1,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 4, end: 7 }
+                            }
+                        })
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 3,
+                        start_col: 1,
+                        end_col: 4,
+                        bytes: ByteSpan { start: 0, end: 11 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_setext_header_after_blank_line() {
+            let input = "one\n\ntwo\n---";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: "one".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 0, end: 3 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 4,
+                            bytes: ByteSpan { start: 0, end: 3 }
+                        }
+                    }),
+                    Node::Eol(Eol {
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 2,
+                            bytes: ByteSpan { start: 4, end: 5 }
+                        }
+                    }),
+                    Node::Header(Header {
+                        level: 2,
+                        nodes: vec![Node::Text(Text {
+                            value: "two".to_string(),
+                            position: LineSpan {
+                                start: 3,
+                                end: 3,
+                                start_col: 1,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 5, end: 8 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 3,
+                            end: 4,
+                            start_col: 1,
+                            end_col: 4,
+                            bytes: ByteSpan { start: 5, end: 12 }
+                        }
+                    })
+                ]
+            )
+        }
+    }
+
+    mod unordered_list_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_unordered_list() {
+            let input = "- item 1\n- item 2\n- item 3\n";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![
+                    Node::UnorderedList(UnorderedList {
+                        level: 0,
+                        checked: None,
+                        nodes: vec![
                             Node::Text(Text {
                                 value: "item".to_string(),
-                                position: LineSpan { start: 1, end: 1 }
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 3,
+                                    end_col: 7,
+                                    bytes: ByteSpan { start: 2, end: 6 }
+                                }
                             }),
                             Node::Whitespace(Whitespace {
-                                position: LineSpan { start: 1, end: 1 }
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 7,
+                                    end_col: 8,
+                                    bytes: ByteSpan { start: 6, end: 7 }
+                                }
                             }),
                             Node::Text(Text {
                                 value: "1".to_string(),
-                                position: LineSpan { start: 1, end: 1 }
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 8,
+                                    end_col: 9,
+                                    bytes: ByteSpan { start: 7, end: 8 }
+                                }
                             }),
                         ],
                         children: vec![],
-                        position: LineSpan { start: 1, end: 1 }
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 9,
+                            bytes: ByteSpan { start: 0, end: 8 }
+                        }
                     }),
                     Node::UnorderedList(UnorderedList {
                         level: 0,
+                        checked: None,
                         nodes: vec![
                             Node::Text(Text {
                                 value: "item".to_string(),
-                                position: LineSpan { start: 2, end: 2 }
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 3,
+                                    end_col: 7,
+                                    bytes: ByteSpan { start: 11, end: 15 }
+                                }
                             }),
                             Node::Whitespace(Whitespace {
-                                position: LineSpan { start: 2, end: 2 }
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 7,
+                                    end_col: 8,
+                                    bytes: ByteSpan { start: 15, end: 16 }
+                                }
                             }),
                             Node::Text(Text {
                                 value: "2".to_string(),
-                                position: LineSpan { start: 2, end: 2 }
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 8,
+                                    end_col: 9,
+                                    bytes: ByteSpan { start: 16, end: 17 }
+                                }
                             }),
                         ],
                         children: vec![],
-                        position: LineSpan { start: 2, end: 2 }
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 9,
+                            bytes: ByteSpan { start: 9, end: 17 }
+                        }
                     }),
                     Node::UnorderedList(UnorderedList {
                         level: 0,
+                        checked: None,
                         nodes: vec![
                             Node::Text(Text {
                                 value: "item".to_string(),
-                                position: LineSpan { start: 3, end: 3 }
+                                position: LineSpan {
+                                    start: 3,
+                                    end: 3,
+                                    start_col: 3,
+                                    end_col: 7,
+                                    bytes: ByteSpan { start: 20, end: 24 }
+                                }
                             }),
                             Node::Whitespace(Whitespace {
-                                position: LineSpan { start: 3, end: 3 }
+                                position: LineSpan {
+                                    start: 3,
+                                    end: 3,
+                                    start_col: 7,
+                                    end_col: 8,
+                                    bytes: ByteSpan { start: 24, end: 25 }
+                                }
                             }),
                             Node::Text(Text {
                                 value: "3".to_string(),
-                                position: LineSpan { start: 3, end: 3 }
+                                position: LineSpan {
+                                    start: 3,
+                                    end: 3,
+                                    start_col: 8,
+                                    end_col: 9,
+                                    bytes: ByteSpan { start: 25, end: 26 }
+                                }
                             }),
                         ],
                         children: vec![],
-                        position: LineSpan { start: 3, end: 3 }
+                        position: LineSpan {
+                            start: 3,
+                            end: 3,
+                            start_col: 1,
+                            end_col: 9,
+                            bytes: ByteSpan { start: 18, end: 26 }
+                        }
                     }),
                 ],
             )
@@ -822,38 +3235,88 @@ mod tests {
                 nodes,
                 vec![Node::UnorderedList(UnorderedList {
                     level: 0,
+                    checked: None,
                     nodes: vec![
                         Node::Text(Text {
                             value: "item".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 2, end: 6 }
+                            }
                         }),
                         Node::Whitespace(Whitespace {
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 7,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 6, end: 7 }
+                            }
                         }),
                         Node::Text(Text {
                             value: "1".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 8,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 7, end: 8 }
+                            }
                         }),
                     ],
                     children: vec![Node::UnorderedList(UnorderedList {
                         level: 1,
+                        checked: None,
                         nodes: vec![
                             Node::Text(Text {
                                 value: "item".to_string(),
-                                position: LineSpan { start: 2, end: 2 }
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 4,
+                                    end_col: 8,
+                                    bytes: ByteSpan { start: 12, end: 16 }
+                                }
                             }),
                             Node::Whitespace(Whitespace {
-                                position: LineSpan { start: 2, end: 2 }
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 8,
+                                    end_col: 9,
+                                    bytes: ByteSpan { start: 16, end: 17 }
+                                }
                             }),
                             Node::Text(Text {
                                 value: "1.1".to_string(),
-                                position: LineSpan { start: 2, end: 2 }
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 9,
+                                    end_col: 12,
+                                    bytes: ByteSpan { start: 17, end: 20 }
+                                }
                             }),
                         ],
                         children: vec![],
-                        position: LineSpan { start: 2, end: 2 }
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 2,
+                            end_col: 12,
+                            bytes: ByteSpan { start: 10, end: 20 }
+                        }
                     }),],
-                    position: LineSpan { start: 1, end: 2 }
+                    position: LineSpan {
+                        start: 1,
+                        end: 2,
+                        start_col: 1,
+                        end_col: 12,
+                        bytes: ByteSpan { start: 0, end: 20 }
+                    }
                 }),],
             )
         }
@@ -867,55 +3330,130 @@ mod tests {
                 nodes,
                 vec![Node::UnorderedList(UnorderedList {
                     level: 0,
+                    checked: None,
                     nodes: vec![
                         Node::Text(Text {
                             value: "item".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 2, end: 6 }
+                            }
                         }),
                         Node::Whitespace(Whitespace {
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 7,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 6, end: 7 }
+                            }
                         }),
                         Node::Text(Text {
                             value: "1".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 8,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 7, end: 8 }
+                            }
                         }),
                     ],
                     children: vec![Node::UnorderedList(UnorderedList {
                         level: 1,
+                        checked: None,
                         nodes: vec![
                             Node::Text(Text {
                                 value: "item".to_string(),
-                                position: LineSpan { start: 2, end: 2 }
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 4,
+                                    end_col: 8,
+                                    bytes: ByteSpan { start: 12, end: 16 }
+                                }
                             }),
                             Node::Whitespace(Whitespace {
-                                position: LineSpan { start: 2, end: 2 }
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 8,
+                                    end_col: 9,
+                                    bytes: ByteSpan { start: 16, end: 17 }
+                                }
                             }),
                             Node::Text(Text {
                                 value: "1.1".to_string(),
-                                position: LineSpan { start: 2, end: 2 }
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 9,
+                                    end_col: 12,
+                                    bytes: ByteSpan { start: 17, end: 20 }
+                                }
                             }),
                         ],
                         children: vec![Node::UnorderedList(UnorderedList {
                             level: 2,
+                            checked: None,
                             nodes: vec![
                                 Node::Text(Text {
                                     value: "item".to_string(),
-                                    position: LineSpan { start: 3, end: 3 }
+                                    position: LineSpan {
+                                        start: 3,
+                                        end: 3,
+                                        start_col: 5,
+                                        end_col: 9,
+                                        bytes: ByteSpan { start: 25, end: 29 }
+                                    }
                                 }),
                                 Node::Whitespace(Whitespace {
-                                    position: LineSpan { start: 3, end: 3 }
+                                    position: LineSpan {
+                                        start: 3,
+                                        end: 3,
+                                        start_col: 9,
+                                        end_col: 10,
+                                        bytes: ByteSpan { start: 29, end: 30 }
+                                    }
                                 }),
                                 Node::Text(Text {
                                     value: "1.1.1".to_string(),
-                                    position: LineSpan { start: 3, end: 3 }
+                                    position: LineSpan {
+                                        start: 3,
+                                        end: 3,
+                                        start_col: 10,
+                                        end_col: 15,
+                                        bytes: ByteSpan { start: 30, end: 35 }
+                                    }
                                 }),
                             ],
                             children: vec![],
-                            position: LineSpan { start: 3, end: 3 }
+                            position: LineSpan {
+                                start: 3,
+                                end: 3,
+                                start_col: 3,
+                                end_col: 15,
+                                bytes: ByteSpan { start: 23, end: 35 }
+                            }
                         }),],
-                        position: LineSpan { start: 2, end: 3 }
+                        position: LineSpan {
+                            start: 2,
+                            end: 3,
+                            start_col: 2,
+                            end_col: 15,
+                            bytes: ByteSpan { start: 10, end: 35 }
+                        }
                     }),],
-                    position: LineSpan { start: 1, end: 3 }
+                    position: LineSpan {
+                        start: 1,
+                        end: 3,
+                        start_col: 1,
+                        end_col: 15,
+                        bytes: ByteSpan { start: 0, end: 35 }
+                    }
                 }),],
             )
         }
@@ -930,29 +3468,68 @@ mod tests {
                 vec![
                     Node::UnorderedList(UnorderedList {
                         level: 0,
+                        checked: None,
                         nodes: vec![Node::Text(Text {
                             value: "item1".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 2, end: 7 }
+                            }
                         }),],
                         children: vec![Node::UnorderedList(UnorderedList {
                             level: 1,
+                            checked: None,
                             nodes: vec![Node::Text(Text {
                                 value: "item1.1".to_string(),
-                                position: LineSpan { start: 2, end: 2 }
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 4,
+                                    end_col: 11,
+                                    bytes: ByteSpan { start: 11, end: 18 }
+                                }
                             }),],
                             children: vec![],
-                            position: LineSpan { start: 2, end: 2 }
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 2,
+                                end_col: 11,
+                                bytes: ByteSpan { start: 9, end: 18 }
+                            }
                         }),],
-                        position: LineSpan { start: 1, end: 2 }
+                        position: LineSpan {
+                            start: 1,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 11,
+                            bytes: ByteSpan { start: 0, end: 18 }
+                        }
                     }),
                     Node::UnorderedList(UnorderedList {
                         level: 0,
+                        checked: None,
                         nodes: vec![Node::Text(Text {
                             value: "item2".to_string(),
-                            position: LineSpan { start: 3, end: 3 }
+                            position: LineSpan {
+                                start: 3,
+                                end: 3,
+                                start_col: 3,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 21, end: 26 }
+                            }
                         }),],
                         children: vec![],
-                        position: LineSpan { start: 3, end: 3 }
+                        position: LineSpan {
+                            start: 3,
+                            end: 3,
+                            start_col: 1,
+                            end_col: 8,
+                            bytes: ByteSpan { start: 19, end: 26 }
+                        }
                     }),
                 ],
             )
@@ -968,110 +3545,260 @@ mod tests {
                 nodes,
                 vec![Node::UnorderedList(UnorderedList {
                     level: 0,
+                    checked: None,
                     nodes: vec![
                         Node::Text(Text {
                             value: "item".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 2, end: 6 }
+                            }
                         }),
                         Node::Whitespace(Whitespace {
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 7,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 6, end: 7 }
+                            }
                         }),
                         Node::Text(Text {
                             value: "1".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 8,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 7, end: 8 }
+                            }
                         }),
                     ],
                     children: vec![
                         Node::UnorderedList(UnorderedList {
                             level: 1,
+                            checked: None,
                             nodes: vec![
                                 Node::Text(Text {
                                     value: "item".to_string(),
-                                    position: LineSpan { start: 2, end: 2 }
+                                    position: LineSpan {
+                                        start: 2,
+                                        end: 2,
+                                        start_col: 4,
+                                        end_col: 8,
+                                        bytes: ByteSpan { start: 12, end: 16 }
+                                    }
                                 }),
                                 Node::Whitespace(Whitespace {
-                                    position: LineSpan { start: 2, end: 2 }
+                                    position: LineSpan {
+                                        start: 2,
+                                        end: 2,
+                                        start_col: 8,
+                                        end_col: 9,
+                                        bytes: ByteSpan { start: 16, end: 17 }
+                                    }
                                 }),
                                 Node::Text(Text {
                                     value: "1.1".to_string(),
-                                    position: LineSpan { start: 2, end: 2 }
+                                    position: LineSpan {
+                                        start: 2,
+                                        end: 2,
+                                        start_col: 9,
+                                        end_col: 12,
+                                        bytes: ByteSpan { start: 17, end: 20 }
+                                    }
                                 }),
                             ],
                             children: vec![],
-                            position: LineSpan { start: 2, end: 2 }
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 2,
+                                end_col: 12,
+                                bytes: ByteSpan { start: 10, end: 20 }
+                            }
                         }),
                         Node::UnorderedList(UnorderedList {
                             level: 1,
+                            checked: None,
                             nodes: vec![
                                 Node::Text(Text {
                                     value: "item".to_string(),
-                                    position: LineSpan { start: 3, end: 3 }
+                                    position: LineSpan {
+                                        start: 3,
+                                        end: 3,
+                                        start_col: 4,
+                                        end_col: 8,
+                                        bytes: ByteSpan { start: 24, end: 28 }
+                                    }
                                 }),
                                 Node::Whitespace(Whitespace {
-                                    position: LineSpan { start: 3, end: 3 }
+                                    position: LineSpan {
+                                        start: 3,
+                                        end: 3,
+                                        start_col: 8,
+                                        end_col: 9,
+                                        bytes: ByteSpan { start: 28, end: 29 }
+                                    }
                                 }),
                                 Node::Text(Text {
                                     value: "1.2".to_string(),
-                                    position: LineSpan { start: 3, end: 3 }
+                                    position: LineSpan {
+                                        start: 3,
+                                        end: 3,
+                                        start_col: 9,
+                                        end_col: 12,
+                                        bytes: ByteSpan { start: 29, end: 32 }
+                                    }
                                 }),
                             ],
                             children: vec![Node::UnorderedList(UnorderedList {
                                 level: 2,
+                                checked: None,
                                 nodes: vec![
                                     Node::Text(Text {
                                         value: "item".to_string(),
-                                        position: LineSpan { start: 4, end: 4 }
+                                        position: LineSpan {
+                                            start: 4,
+                                            end: 4,
+                                            start_col: 5,
+                                            end_col: 9,
+                                            bytes: ByteSpan { start: 37, end: 41 }
+                                        }
                                     }),
                                     Node::Whitespace(Whitespace {
-                                        position: LineSpan { start: 4, end: 4 }
+                                        position: LineSpan {
+                                            start: 4,
+                                            end: 4,
+                                            start_col: 9,
+                                            end_col: 10,
+                                            bytes: ByteSpan { start: 41, end: 42 }
+                                        }
                                     }),
                                     Node::Text(Text {
                                         value: "1.2.1".to_string(),
-                                        position: LineSpan { start: 4, end: 4 }
+                                        position: LineSpan {
+                                            start: 4,
+                                            end: 4,
+                                            start_col: 10,
+                                            end_col: 15,
+                                            bytes: ByteSpan { start: 42, end: 47 }
+                                        }
                                     }),
                                 ],
                                 children: vec![Node::UnorderedList(UnorderedList {
                                     level: 3,
+                                    checked: None,
                                     nodes: vec![
                                         Node::Text(Text {
                                             value: "item".to_string(),
-                                            position: LineSpan { start: 5, end: 5 }
+                                            position: LineSpan {
+                                                start: 5,
+                                                end: 5,
+                                                start_col: 6,
+                                                end_col: 10,
+                                                bytes: ByteSpan { start: 53, end: 57 }
+                                            }
                                         }),
                                         Node::Whitespace(Whitespace {
-                                            position: LineSpan { start: 5, end: 5 }
+                                            position: LineSpan {
+                                                start: 5,
+                                                end: 5,
+                                                start_col: 10,
+                                                end_col: 11,
+                                                bytes: ByteSpan { start: 57, end: 58 }
+                                            }
                                         }),
                                         Node::Text(Text {
                                             value: "1.2.1.1".to_string(),
-                                            position: LineSpan { start: 5, end: 5 }
+                                            position: LineSpan {
+                                                start: 5,
+                                                end: 5,
+                                                start_col: 11,
+                                                end_col: 18,
+                                                bytes: ByteSpan { start: 58, end: 65 }
+                                            }
                                         }),
                                     ],
                                     children: vec![],
-                                    position: LineSpan { start: 5, end: 5 }
+                                    position: LineSpan {
+                                        start: 5,
+                                        end: 5,
+                                        start_col: 4,
+                                        end_col: 18,
+                                        bytes: ByteSpan { start: 51, end: 65 }
+                                    }
                                 }),],
-                                position: LineSpan { start: 4, end: 5 }
+                                position: LineSpan {
+                                    start: 4,
+                                    end: 5,
+                                    start_col: 3,
+                                    end_col: 18,
+                                    bytes: ByteSpan { start: 35, end: 65 }
+                                }
                             }),],
-                            position: LineSpan { start: 3, end: 5 }
+                            position: LineSpan {
+                                start: 3,
+                                end: 5,
+                                start_col: 2,
+                                end_col: 18,
+                                bytes: ByteSpan { start: 22, end: 65 }
+                            }
                         }),
                         Node::UnorderedList(UnorderedList {
                             level: 1,
+                            checked: None,
                             nodes: vec![
                                 Node::Text(Text {
                                     value: "item".to_string(),
-                                    position: LineSpan { start: 6, end: 6 }
+                                    position: LineSpan {
+                                        start: 6,
+                                        end: 6,
+                                        start_col: 4,
+                                        end_col: 8,
+                                        bytes: ByteSpan { start: 69, end: 73 }
+                                    }
                                 }),
                                 Node::Whitespace(Whitespace {
-                                    position: LineSpan { start: 6, end: 6 }
+                                    position: LineSpan {
+                                        start: 6,
+                                        end: 6,
+                                        start_col: 8,
+                                        end_col: 9,
+                                        bytes: ByteSpan { start: 73, end: 74 }
+                                    }
                                 }),
                                 Node::Text(Text {
                                     value: "1.3".to_string(),
-                                    position: LineSpan { start: 6, end: 6 }
+                                    position: LineSpan {
+                                        start: 6,
+                                        end: 6,
+                                        start_col: 9,
+                                        end_col: 12,
+                                        bytes: ByteSpan { start: 74, end: 77 }
+                                    }
                                 }),
                             ],
                             children: vec![],
-                            position: LineSpan { start: 6, end: 6 }
+                            position: LineSpan {
+                                start: 6,
+                                end: 6,
+                                start_col: 2,
+                                end_col: 12,
+                                bytes: ByteSpan { start: 67, end: 77 }
+                            }
                         }),
                     ],
-                    position: LineSpan { start: 1, end: 6 }
+                    position: LineSpan {
+                        start: 1,
+                        end: 6,
+                        start_col: 1,
+                        end_col: 12,
+                        bytes: ByteSpan { start: 0, end: 77 }
+                    }
                 }),],
             )
         }
@@ -1086,18 +3813,42 @@ mod tests {
                 vec![Node::Paragraph(Paragraph {
                     nodes: vec![
                         Node::Whitespace(Whitespace {
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 0, end: 1 }
+                            }
                         }),
                         Node::Text(Text {
                             value: "- ".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 2,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 1, end: 3 }
+                            }
                         }),
                         Node::Text(Text {
                             value: "item1".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 4,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 3, end: 8 }
+                            }
                         }),
                     ],
-                    position: LineSpan { start: 1, end: 1 }
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 9,
+                        bytes: ByteSpan { start: 0, end: 8 }
+                    }
                 },)],
             )
         }
@@ -1118,352 +3869,5151 @@ mod tests {
             let next_nest = stream.is_next_list();
             assert_eq!(next_nest, Some(1));
         }
-    }
-
-    mod alert_tests {
-        use super::*;
-        use pretty_assertions::assert_eq;
 
         #[test]
-        fn test_is_alert() {
-            let test_cases = vec![
-                ("> [!NOTE]", Some(AlertType::Note)),
-                ("> [!TIP]", Some(AlertType::Tip)),
-                ("> [!IMPORTANT]", Some(AlertType::Important)),
-                ("> [!WARNING]", Some(AlertType::Warning)),
-                ("> [!CAUTION]", Some(AlertType::Caution)),
-                ("No alert", None),
-            ];
-
-            for (input, expected) in test_cases {
-                let mut tokens = lex(&input);
-                let mut stream = TokenStream::new(&mut tokens);
+        fn test_unchecked_task_list_item() {
+            let input = "- [ ] todo";
+            let nodes = build_tree(input);
 
-                assert_eq!(
-                    is_alert(&mut stream),
-                    expected,
-                    "Failed on input: {}",
-                    input
-                );
-            }
+            assert_eq!(
+                nodes,
+                vec![Node::UnorderedList(UnorderedList {
+                    level: 0,
+                    checked: Some(false),
+                    nodes: vec![Node::Text(Text {
+                        value: "todo".to_string(),
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 7,
+                            end_col: 11,
+                            bytes: ByteSpan { start: 6, end: 10 }
+                        }
+                    })],
+                    children: vec![],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 11,
+                        bytes: ByteSpan { start: 0, end: 10 }
+                    }
+                })]
+            )
         }
 
         #[test]
-        fn test_is_next_quote() {
-            let test_cases = vec![("\n> quote", true), ("\nNo quote", false)];
+        fn test_checked_task_list_items() {
+            let input = "- [x] done\n- [X] done";
+            let nodes = build_tree(input);
 
-            for (input, expected) in test_cases {
-                let mut tokens = lex(&input);
-                let mut stream = TokenStream::new(&mut tokens);
+            assert_eq!(
+                nodes,
+                vec![
+                    Node::UnorderedList(UnorderedList {
+                        level: 0,
+                        checked: Some(true),
+                        nodes: vec![Node::Text(Text {
+                            value: "done".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 7,
+                                end_col: 11,
+                                bytes: ByteSpan { start: 6, end: 10 }
+                            }
+                        })],
+                        children: vec![],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 11,
+                            bytes: ByteSpan { start: 0, end: 10 }
+                        }
+                    }),
+                    Node::UnorderedList(UnorderedList {
+                        level: 0,
+                        checked: Some(true),
+                        nodes: vec![Node::Text(Text {
+                            value: "done".to_string(),
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 7,
+                                end_col: 11,
+                                bytes: ByteSpan { start: 17, end: 21 }
+                            }
+                        })],
+                        children: vec![],
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 11,
+                            bytes: ByteSpan { start: 11, end: 21 }
+                        }
+                    }),
+                ]
+            )
+        }
 
-                assert!(
-                    is_next_line_quoted(&mut stream) == expected,
-                    "Failed on input: {:?}, expected: {}",
-                    input,
-                    expected
-                );
-            }
+        #[test]
+        fn test_checkbox_after_item_start_is_text() {
+            let input = "- a [x]";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::UnorderedList(UnorderedList {
+                    level: 0,
+                    checked: None,
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "a".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 2, end: 3 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 4,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 3, end: 4 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "[".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 5,
+                                end_col: 6,
+                                bytes: ByteSpan { start: 4, end: 5 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "x".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 6,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 5, end: 6 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "]".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 7,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 6, end: 7 }
+                            }
+                        })
+                    ],
+                    children: vec![],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 8,
+                        bytes: ByteSpan { start: 0, end: 7 }
+                    }
+                })]
+            )
         }
 
         #[test]
-        fn test_alert() {
-            let input = "> [!NOTE]\n> note content\n> note content";
+        fn test_tab_indented_nested_list() {
+            let parser = Parser::new();
+
+            assert_eq!(
+                clear_positions(parser.parse("- item 1\n\t- item 1.1\n\t\t- item 1.1.1\n- item 2")),
+                clear_positions(
+                    parser.parse("- item 1\n    - item 1.1\n        - item 1.1.1\n- item 2")
+                )
+            );
+            assert_eq!(
+                clear_positions(
+                    parser
+                        .clone()
+                        .tab_width(1)
+                        .parse("- item 1\n\t- item 1.1\n\t\t- item 1.1.1")
+                ),
+                clear_positions(parser.parse("- item 1\n - item 1.1\n  - item 1.1.1"))
+            );
+        }
+
+        #[test]
+        fn test_continuation_paragraph() {
+            let input = "- a\n\n  more text\n- b";
             let nodes = build_tree(input);
 
             assert_eq!(
                 nodes,
-                vec![Node::Alert(Alert {
-                    alert_type: AlertType::Note,
+                vec![
+                    Node::UnorderedList(UnorderedList {
+                        level: 0,
+                        checked: None,
+                        nodes: vec![
+                            Node::Text(Text {
+                                value: "a".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 3,
+                                    end_col: 4,
+                                    bytes: ByteSpan { start: 2, end: 3 }
+                                }
+                            }),
+                            Node::Paragraph(Paragraph {
+                                nodes: vec![
+                                    Node::Text(Text {
+                                        value: "more".to_string(),
+                                        position: LineSpan {
+                                            start: 3,
+                                            end: 3,
+                                            start_col: 3,
+                                            end_col: 7,
+                                            bytes: ByteSpan { start: 7, end: 11 }
+                                        }
+                                    }),
+                                    Node::Whitespace(Whitespace {
+                                        position: LineSpan {
+                                            start: 3,
+                                            end: 3,
+                                            start_col: 7,
+                                            end_col: 8,
+                                            bytes: ByteSpan { start: 11, end: 12 }
+                                        }
+                                    }),
+                                    Node::Text(Text {
+                                        value: "text".to_string(),
+                                        position: LineSpan {
+                                            start: 3,
+                                            end: 3,
+                                            start_col: 8,
+                                            end_col: 12,
+                                            bytes: ByteSpan { start: 12, end: 16 }
+                                        }
+                                    })
+                                ],
+                                position: LineSpan {
+                                    start: 3,
+                                    end: 3,
+                                    start_col: 3,
+                                    end_col: 12,
+                                    bytes: ByteSpan { start: 7, end: 16 }
+                                }
+                            })
+                        ],
+                        children: vec![],
+                        position: LineSpan {
+                            start: 1,
+                            end: 3,
+                            start_col: 1,
+                            end_col: 12,
+                            bytes: ByteSpan { start: 0, end: 16 }
+                        }
+                    }),
+                    Node::UnorderedList(UnorderedList {
+                        level: 0,
+                        checked: None,
+                        nodes: vec![Node::Text(Text {
+                            value: "b".to_string(),
+                            position: LineSpan {
+                                start: 4,
+                                end: 4,
+                                start_col: 3,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 19, end: 20 }
+                            }
+                        })],
+                        children: vec![],
+                        position: LineSpan {
+                            start: 4,
+                            end: 4,
+                            start_col: 1,
+                            end_col: 4,
+                            bytes: ByteSpan { start: 17, end: 20 }
+                        }
+                    })
+                ]
+            )
+        }
+
+        #[test]
+        fn test_continuation_without_blank_line() {
+            let input = "- item\n  continuation paragraph";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::UnorderedList(UnorderedList {
+                    level: 0,
+                    checked: None,
                     nodes: vec![
                         Node::Text(Text {
-                            value: "note".to_string(),
-                            position: LineSpan { start: 2, end: 2 }
+                            value: "item".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 2, end: 6 }
+                            }
+                        }),
+                        Node::Paragraph(Paragraph {
+                            nodes: vec![
+                                Node::Text(Text {
+                                    value: "continuation".to_string(),
+                                    position: LineSpan {
+                                        start: 2,
+                                        end: 2,
+                                        start_col: 3,
+                                        end_col: 15,
+                                        bytes: ByteSpan { start: 9, end: 21 }
+                                    }
+                                }),
+                                Node::Whitespace(Whitespace {
+                                    position: LineSpan {
+                                        start: 2,
+                                        end: 2,
+                                        start_col: 15,
+                                        end_col: 16,
+                                        bytes: ByteSpan { start: 21, end: 22 }
+                                    }
+                                }),
+                                Node::Text(Text {
+                                    value: "paragraph".to_string(),
+                                    position: LineSpan {
+                                        start: 2,
+                                        end: 2,
+                                        start_col: 16,
+                                        end_col: 25,
+                                        bytes: ByteSpan { start: 22, end: 31 }
+                                    }
+                                })
+                            ],
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 3,
+                                end_col: 25,
+                                bytes: ByteSpan { start: 9, end: 31 }
+                            }
+                        })
+                    ],
+                    children: vec![],
+                    position: LineSpan {
+                        start: 1,
+                        end: 2,
+                        start_col: 1,
+                        end_col: 25,
+                        bytes: ByteSpan { start: 0, end: 31 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_hard_break_in_list_item() {
+            let input = "- line one  \n  line two";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::UnorderedList(UnorderedList {
+                    level: 0,
+                    checked: None,
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "line".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 2, end: 6 }
+                            }
                         }),
                         Node::Whitespace(Whitespace {
-                            position: LineSpan { start: 2, end: 2 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 7,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 6, end: 7 }
+                            }
                         }),
                         Node::Text(Text {
-                            value: "content".to_string(),
-                            position: LineSpan { start: 2, end: 2 }
+                            value: "one".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 8,
+                                end_col: 11,
+                                bytes: ByteSpan { start: 7, end: 10 }
+                            }
                         }),
-                        Node::Eol(Eol {
-                            position: LineSpan { start: 2, end: 2 }
+                        Node::HardBreak(HardBreak {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 11,
+                                end_col: 13,
+                                bytes: ByteSpan { start: 10, end: 12 }
+                            }
                         }),
                         Node::Text(Text {
-                            value: "note".to_string(),
-                            position: LineSpan { start: 3, end: 3 }
+                            value: "line".to_string(),
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 3,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 15, end: 19 }
+                            }
                         }),
                         Node::Whitespace(Whitespace {
-                            position: LineSpan { start: 3, end: 3 }
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 7,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 19, end: 20 }
+                            }
                         }),
                         Node::Text(Text {
-                            value: "content".to_string(),
-                            position: LineSpan { start: 3, end: 3 }
+                            value: "two".to_string(),
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 8,
+                                end_col: 11,
+                                bytes: ByteSpan { start: 20, end: 23 }
+                            }
                         }),
                     ],
-                    position: LineSpan { start: 1, end: 3 }
-                })],
+                    children: vec![],
+                    position: LineSpan {
+                        start: 1,
+                        end: 2,
+                        start_col: 1,
+                        end_col: 11,
+                        bytes: ByteSpan { start: 0, end: 23 }
+                    }
+                })]
             )
         }
     }
 
-    mod styled_text_tests {
+    mod ordered_list_tests {
         use super::*;
         use pretty_assertions::assert_eq;
 
         #[test]
-        fn test_italic_and_plain_text() {
-            let input = "*italic* text";
+        fn test_ordered_list() {
+            let input = "1. first\n2. second\n3. third";
             let nodes = build_tree(input);
 
             assert_eq!(
                 nodes,
-                vec![Node::Paragraph(Paragraph {
-                    nodes: vec![
-                        Node::Italic(Italic {
+                vec![
+                    Node::OrderedList(OrderedList {
+                        level: 0,
+                        start: 1,
+                        nodes: vec![Node::Text(Text {
+                            value: "first".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 4,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 3, end: 8 }
+                            }
+                        })],
+                        children: vec![],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 9,
+                            bytes: ByteSpan { start: 0, end: 8 }
+                        }
+                    }),
+                    Node::OrderedList(OrderedList {
+                        level: 0,
+                        start: 2,
+                        nodes: vec![Node::Text(Text {
+                            value: "second".to_string(),
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 4,
+                                end_col: 10,
+                                bytes: ByteSpan { start: 12, end: 18 }
+                            }
+                        })],
+                        children: vec![],
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 10,
+                            bytes: ByteSpan { start: 9, end: 18 }
+                        }
+                    }),
+                    Node::OrderedList(OrderedList {
+                        level: 0,
+                        start: 3,
+                        nodes: vec![Node::Text(Text {
+                            value: "third".to_string(),
+                            position: LineSpan {
+                                start: 3,
+                                end: 3,
+                                start_col: 4,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 22, end: 27 }
+                            }
+                        })],
+                        children: vec![],
+                        position: LineSpan {
+                            start: 3,
+                            end: 3,
+                            start_col: 1,
+                            end_col: 9,
+                            bytes: ByteSpan { start: 19, end: 27 }
+                        }
+                    }),
+                ],
+            )
+        }
+
+        #[test]
+        fn test_ordered_list_with_custom_start() {
+            let input = "7. seventh\n";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::OrderedList(OrderedList {
+                    level: 0,
+                    start: 7,
+                    nodes: vec![Node::Text(Text {
+                        value: "seventh".to_string(),
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 4,
+                            end_col: 11,
+                            bytes: ByteSpan { start: 3, end: 10 }
+                        }
+                    })],
+                    children: vec![],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 11,
+                        bytes: ByteSpan { start: 0, end: 10 }
+                    }
+                })],
+            )
+        }
+
+        #[test]
+        fn test_mixed_nested_list() {
+            let input = "1. one\n - bullet\n  1. deep\n2. two";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![
+                    Node::OrderedList(OrderedList {
+                        level: 0,
+                        start: 1,
+                        nodes: vec![Node::Text(Text {
+                            value: "one".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 4,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 3, end: 6 }
+                            }
+                        })],
+                        children: vec![Node::UnorderedList(UnorderedList {
+                            level: 1,
+                            checked: None,
                             nodes: vec![Node::Text(Text {
-                                value: "italic".to_string(),
-                                position: LineSpan { start: 1, end: 1 }
-                            }),],
-                            position: LineSpan { start: 1, end: 1 }
-                        }),
-                        Node::Whitespace(Whitespace {
-                            position: LineSpan { start: 1, end: 1 }
-                        }),
-                        Node::Text(Text {
-                            value: "text".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
-                        }),
-                    ],
-                    position: LineSpan { start: 1, end: 1 }
-                },)],
+                                value: "bullet".to_string(),
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 4,
+                                    end_col: 10,
+                                    bytes: ByteSpan { start: 10, end: 16 }
+                                }
+                            })],
+                            children: vec![Node::OrderedList(OrderedList {
+                                level: 2,
+                                start: 1,
+                                nodes: vec![Node::Text(Text {
+                                    value: "deep".to_string(),
+                                    position: LineSpan {
+                                        start: 3,
+                                        end: 3,
+                                        start_col: 6,
+                                        end_col: 10,
+                                        bytes: ByteSpan { start: 22, end: 26 }
+                                    }
+                                })],
+                                children: vec![],
+                                position: LineSpan {
+                                    start: 3,
+                                    end: 3,
+                                    start_col: 3,
+                                    end_col: 10,
+                                    bytes: ByteSpan { start: 19, end: 26 }
+                                }
+                            })],
+                            position: LineSpan {
+                                start: 2,
+                                end: 3,
+                                start_col: 2,
+                                end_col: 10,
+                                bytes: ByteSpan { start: 8, end: 26 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 3,
+                            start_col: 1,
+                            end_col: 10,
+                            bytes: ByteSpan { start: 0, end: 26 }
+                        }
+                    }),
+                    Node::OrderedList(OrderedList {
+                        level: 0,
+                        start: 2,
+                        nodes: vec![Node::Text(Text {
+                            value: "two".to_string(),
+                            position: LineSpan {
+                                start: 4,
+                                end: 4,
+                                start_col: 4,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 30, end: 33 }
+                            }
+                        })],
+                        children: vec![],
+                        position: LineSpan {
+                            start: 4,
+                            end: 4,
+                            start_col: 1,
+                            end_col: 7,
+                            bytes: ByteSpan { start: 27, end: 33 }
+                        }
+                    }),
+                ],
             )
         }
+    }
+
+    mod indentation_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
 
         #[test]
-        fn test_bold_and_plain_text() {
-            let input = "**bold** text";
+        fn test_indented_ordered_marker_without_list() {
+            let input = "  1. one";
             let nodes = build_tree(input);
 
             assert_eq!(
                 nodes,
                 vec![Node::Paragraph(Paragraph {
                     nodes: vec![
-                        Node::Bold(Bold {
-                            nodes: vec![Node::Text(Text {
-                                value: "bold".to_string(),
-                                position: LineSpan { start: 1, end: 1 }
-                            }),],
-                            position: LineSpan { start: 1, end: 1 }
-                        }),
                         Node::Whitespace(Whitespace {
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 3,
+                                bytes: ByteSpan { start: 0, end: 2 }
+                            }
                         }),
                         Node::Text(Text {
-                            value: "text".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            value: "1.".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 2, end: 4 }
+                            }
                         }),
-                    ],
-                    position: LineSpan { start: 1, end: 1 }
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 5,
+                                end_col: 6,
+                                bytes: ByteSpan { start: 4, end: 5 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "one".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 6,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 5, end: 8 }
+                            }
+                        })
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 9,
+                        bytes: ByteSpan { start: 0, end: 8 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_indented_marker_after_paragraph() {
+            let input = "text\n  - item";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: "text".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 0, end: 4 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 5,
+                            bytes: ByteSpan { start: 0, end: 4 }
+                        }
+                    }),
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![
+                            Node::Whitespace(Whitespace {
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 1,
+                                    end_col: 3,
+                                    bytes: ByteSpan { start: 5, end: 7 }
+                                }
+                            }),
+                            Node::Text(Text {
+                                value: "- ".to_string(),
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 3,
+                                    end_col: 5,
+                                    bytes: ByteSpan { start: 7, end: 9 }
+                                }
+                            }),
+                            Node::Text(Text {
+                                value: "item".to_string(),
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 5,
+                                    end_col: 9,
+                                    bytes: ByteSpan { start: 9, end: 13 }
+                                }
+                            })
+                        ],
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 9,
+                            bytes: ByteSpan { start: 5, end: 13 }
+                        }
+                    })
+                ]
+            )
+        }
+
+        #[test]
+        fn test_indented_text_after_header() {
+            let input = "# Title\n   text";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![
+                    Node::Header(Header {
+                        level: 1,
+                        nodes: vec![Node::Text(Text {
+                            value: "Title".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 2, end: 7 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 8,
+                            bytes: ByteSpan { start: 0, end: 7 }
+                        }
+                    }),
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![
+                            Node::Whitespace(Whitespace {
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 1,
+                                    end_col: 4,
+                                    bytes: ByteSpan { start: 8, end: 11 }
+                                }
+                            }),
+                            Node::Text(Text {
+                                value: "text".to_string(),
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 4,
+                                    end_col: 8,
+                                    bytes: ByteSpan { start: 11, end: 15 }
+                                }
+                            })
+                        ],
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 8,
+                            bytes: ByteSpan { start: 8, end: 15 }
+                        }
+                    })
+                ]
+            )
+        }
+    }
+
+    mod alert_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_is_alert() {
+            let test_cases = vec![
+                ("> [!NOTE]", Some(AlertType::Note)),
+                ("> [!TIP]", Some(AlertType::Tip)),
+                ("> [!IMPORTANT]", Some(AlertType::Important)),
+                ("> [!WARNING]", Some(AlertType::Warning)),
+                ("> [!CAUTION]", Some(AlertType::Caution)),
+                ("No alert", None),
+            ];
+
+            for (input, expected) in test_cases {
+                let mut tokens = lex(input);
+                let mut stream = TokenStream::new(&mut tokens);
+
+                assert_eq!(
+                    is_alert(&mut stream),
+                    expected,
+                    "Failed on input: {}",
+                    input
+                );
+            }
+        }
+
+        #[test]
+        fn test_is_next_quote() {
+            let test_cases = vec![("\n> quote", true), ("\nNo quote", false)];
+
+            for (input, expected) in test_cases {
+                let mut tokens = lex(input);
+                let mut stream = TokenStream::new(&mut tokens);
+
+                assert!(
+                    is_next_line_quoted(&mut stream) == expected,
+                    "Failed on input: {:?}, expected: {}",
+                    input,
+                    expected
+                );
+            }
+        }
+
+        #[test]
+        fn test_alert() {
+            let input = "> [!NOTE]\n> note content\n> note content";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Alert(Alert {
+                    alert_type: AlertType::Note,
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "note".to_string(),
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 3,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 12, end: 16 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 7,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 16, end: 17 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "content".to_string(),
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 8,
+                                end_col: 15,
+                                bytes: ByteSpan { start: 17, end: 24 }
+                            }
+                        }),
+                        Node::Eol(Eol {
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 15,
+                                end_col: 16,
+                                bytes: ByteSpan { start: 24, end: 25 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "note".to_string(),
+                            position: LineSpan {
+                                start: 3,
+                                end: 3,
+                                start_col: 3,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 27, end: 31 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 3,
+                                end: 3,
+                                start_col: 7,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 31, end: 32 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "content".to_string(),
+                            position: LineSpan {
+                                start: 3,
+                                end: 3,
+                                start_col: 8,
+                                end_col: 15,
+                                bytes: ByteSpan { start: 32, end: 39 }
+                            }
+                        }),
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 3,
+                        start_col: 1,
+                        end_col: 15,
+                        bytes: ByteSpan { start: 0, end: 39 }
+                    }
+                })],
+            )
+        }
+
+        #[test]
+        fn test_alert_block() {
+            let input = ":::Warning\ncareful\n:::\ntext";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![
+                    Node::Alert(Alert {
+                        alert_type: AlertType::Warning,
+                        nodes: vec![Node::Paragraph(Paragraph {
+                            nodes: vec![Node::Text(Text {
+                                value: "careful".to_string(),
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 1,
+                                    end_col: 8,
+                                    bytes: ByteSpan { start: 11, end: 18 }
+                                }
+                            })],
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 1,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 11, end: 18 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 3,
+                            start_col: 1,
+                            end_col: 4,
+                            bytes: ByteSpan { start: 0, end: 22 }
+                        }
+                    }),
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: "text".to_string(),
+                            position: LineSpan {
+                                start: 4,
+                                end: 4,
+                                start_col: 1,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 23, end: 27 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 4,
+                            end: 4,
+                            start_col: 1,
+                            end_col: 5,
+                            bytes: ByteSpan { start: 23, end: 27 }
+                        }
+                    }),
+                ],
+            )
+        }
+
+        #[test]
+        fn test_alert_block_without_type() {
+            let input = ":::\nnote\n:::";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Alert(Alert {
+                    alert_type: AlertType::Note,
+                    nodes: vec![Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: "note".to_string(),
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 1,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 4, end: 8 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 5,
+                            bytes: ByteSpan { start: 4, end: 8 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 3,
+                        start_col: 1,
+                        end_col: 4,
+                        bytes: ByteSpan { start: 0, end: 12 }
+                    }
+                })],
+            )
+        }
+
+        #[test]
+        fn test_alert_block_with_paragraphs() {
+            let input = ":::tip\n# Tip\none\n\ntwo";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Alert(Alert {
+                    alert_type: AlertType::Tip,
+                    nodes: vec![
+                        Node::Header(Header {
+                            level: 1,
+                            nodes: vec![Node::Text(Text {
+                                value: "Tip".to_string(),
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 3,
+                                    end_col: 6,
+                                    bytes: ByteSpan { start: 9, end: 12 }
+                                }
+                            })],
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 1,
+                                end_col: 6,
+                                bytes: ByteSpan { start: 7, end: 12 }
+                            }
+                        }),
+                        Node::Paragraph(Paragraph {
+                            nodes: vec![Node::Text(Text {
+                                value: "one".to_string(),
+                                position: LineSpan {
+                                    start: 3,
+                                    end: 3,
+                                    start_col: 1,
+                                    end_col: 4,
+                                    bytes: ByteSpan { start: 13, end: 16 }
+                                }
+                            })],
+                            position: LineSpan {
+                                start: 3,
+                                end: 3,
+                                start_col: 1,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 13, end: 16 }
+                            }
+                        }),
+                        Node::Eol(Eol {
+                            position: LineSpan {
+                                start: 4,
+                                end: 4,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 17, end: 18 }
+                            }
+                        }),
+                        Node::Paragraph(Paragraph {
+                            nodes: vec![Node::Text(Text {
+                                value: "two".to_string(),
+                                position: LineSpan {
+                                    start: 5,
+                                    end: 5,
+                                    start_col: 1,
+                                    end_col: 4,
+                                    bytes: ByteSpan { start: 18, end: 21 }
+                                }
+                            })],
+                            position: LineSpan {
+                                start: 5,
+                                end: 5,
+                                start_col: 1,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 18, end: 21 }
+                            }
+                        }),
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 5,
+                        start_col: 1,
+                        end_col: 4,
+                        bytes: ByteSpan { start: 0, end: 21 }
+                    }
+                })],
+            )
+        }
+
+        #[test]
+        fn test_alert_block_with_unknown_type() {
+            let input = ":::foo";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Text(Text {
+                        value: ":::foo".to_string(),
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 7,
+                            bytes: ByteSpan { start: 0, end: 6 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 7,
+                        bytes: ByteSpan { start: 0, end: 6 }
+                    }
+                })],
+            )
+        }
+    }
+
+    mod styled_text_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_italic_and_plain_text() {
+            let input = "*italic* text";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Italic(Italic {
+                            nodes: vec![Node::Text(Text {
+                                value: "italic".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 2,
+                                    end_col: 8,
+                                    bytes: ByteSpan { start: 1, end: 7 }
+                                }
+                            }),],
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 0, end: 8 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 9,
+                                end_col: 10,
+                                bytes: ByteSpan { start: 8, end: 9 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "text".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 10,
+                                end_col: 14,
+                                bytes: ByteSpan { start: 9, end: 13 }
+                            }
+                        }),
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 14,
+                        bytes: ByteSpan { start: 0, end: 13 }
+                    }
+                },)],
+            )
+        }
+
+        #[test]
+        fn test_bold_and_plain_text() {
+            let input = "**bold** text";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Bold(Bold {
+                            nodes: vec![Node::Text(Text {
+                                value: "bold".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 3,
+                                    end_col: 7,
+                                    bytes: ByteSpan { start: 2, end: 6 }
+                                }
+                            }),],
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 0, end: 8 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 9,
+                                end_col: 10,
+                                bytes: ByteSpan { start: 8, end: 9 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "text".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 10,
+                                end_col: 14,
+                                bytes: ByteSpan { start: 9, end: 13 }
+                            }
+                        }),
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 14,
+                        bytes: ByteSpan { start: 0, end: 13 }
+                    }
+                },)],
+            )
+        }
+
+        #[test]
+        fn test_multiple_text() {
+            let input = "**bold**\n*italic*\nplain";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Bold(Bold {
+                            nodes: vec![Node::Text(Text {
+                                value: "bold".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 3,
+                                    end_col: 7,
+                                    bytes: ByteSpan { start: 2, end: 6 }
+                                }
+                            }),],
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 0, end: 8 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 9,
+                            bytes: ByteSpan { start: 0, end: 8 }
+                        }
+                    },),
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Italic(Italic {
+                            nodes: vec![Node::Text(Text {
+                                value: "italic".to_string(),
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 2,
+                                    end_col: 8,
+                                    bytes: ByteSpan { start: 10, end: 16 }
+                                }
+                            }),],
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 1,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 9, end: 17 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 9,
+                            bytes: ByteSpan { start: 9, end: 17 }
+                        }
+                    },),
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: "plain".to_string(),
+                            position: LineSpan {
+                                start: 3,
+                                end: 3,
+                                start_col: 1,
+                                end_col: 6,
+                                bytes: ByteSpan { start: 18, end: 23 }
+                            }
+                        }),],
+                        position: LineSpan {
+                            start: 3,
+                            end: 3,
+                            start_col: 1,
+                            end_col: 6,
+                            bytes: ByteSpan { start: 18, end: 23 }
+                        }
+                    },)
+                ],
+            )
+        }
+
+        #[test]
+        fn test_closed_italic_marker() {
+            let input = "*italic text*";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Italic(Italic {
+                        nodes: vec![
+                            Node::Text(Text {
+                                value: "italic".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 2,
+                                    end_col: 8,
+                                    bytes: ByteSpan { start: 1, end: 7 }
+                                }
+                            }),
+                            Node::Whitespace(Whitespace {
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 8,
+                                    end_col: 9,
+                                    bytes: ByteSpan { start: 7, end: 8 }
+                                }
+                            }),
+                            Node::Text(Text {
+                                value: "text".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 9,
+                                    end_col: 13,
+                                    bytes: ByteSpan { start: 8, end: 12 }
+                                }
+                            }),
+                        ],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 14,
+                            bytes: ByteSpan { start: 0, end: 13 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 14,
+                        bytes: ByteSpan { start: 0, end: 13 }
+                    }
+                },)],
+            )
+        }
+
+        #[test]
+        fn test_unclosed_italic_marker() {
+            let input = "*italic text";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "*".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 0, end: 1 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "italic".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 2,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 1, end: 7 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 8,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 7, end: 8 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "text".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 9,
+                                end_col: 13,
+                                bytes: ByteSpan { start: 8, end: 12 }
+                            }
+                        }),
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 13,
+                        bytes: ByteSpan { start: 0, end: 12 }
+                    }
+                },)],
+            )
+        }
+
+        #[test]
+        fn test_unclosed_underscore_bold_marker() {
+            let input = "__bold";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "__".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 3,
+                                bytes: ByteSpan { start: 0, end: 2 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "bold".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 2, end: 6 }
+                            }
+                        }),
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 7,
+                        bytes: ByteSpan { start: 0, end: 6 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_unmatched_italic_marker() {
+            let input = "italic text*";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "italic".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 0, end: 6 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 7,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 6, end: 7 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "text".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 8,
+                                end_col: 12,
+                                bytes: ByteSpan { start: 7, end: 11 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "*".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 12,
+                                end_col: 13,
+                                bytes: ByteSpan { start: 11, end: 12 }
+                            }
+                        }),
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 13,
+                        bytes: ByteSpan { start: 0, end: 12 }
+                    }
+                },)],
+            )
+        }
+
+        #[test]
+        fn test_closed_bold_marker() {
+            let input = "**bold text**";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Bold(Bold {
+                        nodes: vec![
+                            Node::Text(Text {
+                                value: "bold".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 3,
+                                    end_col: 7,
+                                    bytes: ByteSpan { start: 2, end: 6 }
+                                }
+                            }),
+                            Node::Whitespace(Whitespace {
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 7,
+                                    end_col: 8,
+                                    bytes: ByteSpan { start: 6, end: 7 }
+                                }
+                            }),
+                            Node::Text(Text {
+                                value: "text".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 8,
+                                    end_col: 12,
+                                    bytes: ByteSpan { start: 7, end: 11 }
+                                }
+                            }),
+                        ],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 14,
+                            bytes: ByteSpan { start: 0, end: 13 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 14,
+                        bytes: ByteSpan { start: 0, end: 13 }
+                    }
+                },)],
+            )
+        }
+
+        #[test]
+        fn test_unclosed_bold_marker() {
+            let input = "**bold text";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "**".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 3,
+                                bytes: ByteSpan { start: 0, end: 2 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "bold".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 2, end: 6 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 7,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 6, end: 7 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "text".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 8,
+                                end_col: 12,
+                                bytes: ByteSpan { start: 7, end: 11 }
+                            }
+                        }),
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 12,
+                        bytes: ByteSpan { start: 0, end: 11 }
+                    }
+                },)],
+            )
+        }
+
+        #[test]
+        fn test_strikethrough() {
+            let input = "~~gone~~";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Strikethrough(Strikethrough {
+                        nodes: vec![Node::Text(Text {
+                            value: "gone".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 2, end: 6 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 9,
+                            bytes: ByteSpan { start: 0, end: 8 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 9,
+                        bytes: ByteSpan { start: 0, end: 8 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_unclosed_strikethrough_marker() {
+            let input = "~~gone";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "~~".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 3,
+                                bytes: ByteSpan { start: 0, end: 2 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "gone".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 2, end: 6 }
+                            }
+                        }),
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 7,
+                        bytes: ByteSpan { start: 0, end: 6 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_single_tilde_is_text() {
+            let input = "a~b";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Text(Text {
+                        value: "a~b".to_string(),
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 4,
+                            bytes: ByteSpan { start: 0, end: 3 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 4,
+                        bytes: ByteSpan { start: 0, end: 3 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_unclosed_italic_marker_on_second_line() {
+            let input = "x\n*unclosed";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: "x".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 0, end: 1 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 2,
+                            bytes: ByteSpan { start: 0, end: 1 }
+                        }
+                    }),
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![
+                            Node::Text(Text {
+                                value: "*".to_string(),
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 1,
+                                    end_col: 2,
+                                    bytes: ByteSpan { start: 2, end: 3 }
+                                }
+                            }),
+                            Node::Text(Text {
+                                value: "unclosed".to_string(),
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 2,
+                                    end_col: 10,
+                                    bytes: ByteSpan { start: 3, end: 11 }
+                                }
+                            })
+                        ],
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 10,
+                            bytes: ByteSpan { start: 2, end: 11 }
+                        }
+                    }),
+                ]
+            )
+        }
+
+        #[test]
+        fn test_asterisk_between_spaces_is_literal() {
+            let input = "a * b";
+            let (nodes, diagnostics) = build_tree_with_diagnostics(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "a".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 0, end: 1 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 2,
+                                end_col: 3,
+                                bytes: ByteSpan { start: 1, end: 2 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "*".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 2, end: 3 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 4,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 3, end: 4 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "b".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 5,
+                                end_col: 6,
+                                bytes: ByteSpan { start: 4, end: 5 }
+                            }
+                        })
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 6,
+                        bytes: ByteSpan { start: 0, end: 5 }
+                    }
+                })]
+            );
+            assert_eq!(diagnostics, vec![]);
+        }
+
+        #[test]
+        fn test_italic_single_word() {
+            let input = "*x*";
+            let (nodes, diagnostics) = build_tree_with_diagnostics(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Italic(Italic {
+                        nodes: vec![Node::Text(Text {
+                            value: "x".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 2,
+                                end_col: 3,
+                                bytes: ByteSpan { start: 1, end: 2 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 4,
+                            bytes: ByteSpan { start: 0, end: 3 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 4,
+                        bytes: ByteSpan { start: 0, end: 3 }
+                    }
+                })]
+            );
+            assert_eq!(diagnostics, vec![]);
+        }
+
+        #[test]
+        fn test_asterisks_next_to_spaces_are_not_italic() {
+            let input = "* not italic *";
+            let (nodes, diagnostics) = build_tree_with_diagnostics(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "*".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 0, end: 1 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 2,
+                                end_col: 3,
+                                bytes: ByteSpan { start: 1, end: 2 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "not".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 6,
+                                bytes: ByteSpan { start: 2, end: 5 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 6,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 5, end: 6 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "italic".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 7,
+                                end_col: 13,
+                                bytes: ByteSpan { start: 6, end: 12 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 13,
+                                end_col: 14,
+                                bytes: ByteSpan { start: 12, end: 13 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "*".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 14,
+                                end_col: 15,
+                                bytes: ByteSpan { start: 13, end: 14 }
+                            }
+                        })
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 15,
+                        bytes: ByteSpan { start: 0, end: 14 }
+                    }
+                })]
+            );
+            assert_eq!(diagnostics, vec![]);
+        }
+
+        #[test]
+        fn test_italic_inside_bold() {
+            let input = "**a *b* c**";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Bold(Bold {
+                        nodes: vec![
+                            Node::Text(Text {
+                                value: "a".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 3,
+                                    end_col: 4,
+                                    bytes: ByteSpan { start: 2, end: 3 }
+                                }
+                            }),
+                            Node::Whitespace(Whitespace {
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 4,
+                                    end_col: 5,
+                                    bytes: ByteSpan { start: 3, end: 4 }
+                                }
+                            }),
+                            Node::Italic(Italic {
+                                nodes: vec![Node::Text(Text {
+                                    value: "b".to_string(),
+                                    position: LineSpan {
+                                        start: 1,
+                                        end: 1,
+                                        start_col: 6,
+                                        end_col: 7,
+                                        bytes: ByteSpan { start: 5, end: 6 }
+                                    }
+                                })],
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 5,
+                                    end_col: 8,
+                                    bytes: ByteSpan { start: 4, end: 7 }
+                                }
+                            }),
+                            Node::Whitespace(Whitespace {
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 8,
+                                    end_col: 9,
+                                    bytes: ByteSpan { start: 7, end: 8 }
+                                }
+                            }),
+                            Node::Text(Text {
+                                value: "c".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 9,
+                                    end_col: 10,
+                                    bytes: ByteSpan { start: 8, end: 9 }
+                                }
+                            })
+                        ],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 12,
+                            bytes: ByteSpan { start: 0, end: 11 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 12,
+                        bytes: ByteSpan { start: 0, end: 11 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_mismatched_closing_marker_is_literal() {
+            for input in ["*foo_ bar", "__foo** bar"] {
+                let nodes = build_tree(input);
+                let Node::Paragraph(paragraph) = &nodes[0] else {
+                    panic!("expected a paragraph, got {:?}", nodes[0]);
+                };
+                assert!(
+                    paragraph
+                        .nodes
+                        .iter()
+                        .all(|node| matches!(node, Node::Text(_) | Node::Whitespace(_))),
+                    "{input:?} parsed as {:?}",
+                    paragraph.nodes
+                );
+                assert_eq!(plain_text(&paragraph.nodes), input);
+            }
+        }
+
+        #[test]
+        fn test_italic_closing_inside_bold() {
+            let input = "**bold *and italic***";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Bold(Bold {
+                        nodes: vec![
+                            Node::Text(Text {
+                                value: "bold".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 3,
+                                    end_col: 7,
+                                    bytes: ByteSpan { start: 2, end: 6 }
+                                }
+                            }),
+                            Node::Whitespace(Whitespace {
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 7,
+                                    end_col: 8,
+                                    bytes: ByteSpan { start: 6, end: 7 }
+                                }
+                            }),
+                            Node::Italic(Italic {
+                                nodes: vec![
+                                    Node::Text(Text {
+                                        value: "and".to_string(),
+                                        position: LineSpan {
+                                            start: 1,
+                                            end: 1,
+                                            start_col: 9,
+                                            end_col: 12,
+                                            bytes: ByteSpan { start: 8, end: 11 }
+                                        }
+                                    }),
+                                    Node::Whitespace(Whitespace {
+                                        position: LineSpan {
+                                            start: 1,
+                                            end: 1,
+                                            start_col: 12,
+                                            end_col: 13,
+                                            bytes: ByteSpan { start: 11, end: 12 }
+                                        }
+                                    }),
+                                    Node::Text(Text {
+                                        value: "italic".to_string(),
+                                        position: LineSpan {
+                                            start: 1,
+                                            end: 1,
+                                            start_col: 13,
+                                            end_col: 19,
+                                            bytes: ByteSpan { start: 12, end: 18 }
+                                        }
+                                    }),
+                                ],
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 8,
+                                    end_col: 20,
+                                    bytes: ByteSpan { start: 7, end: 19 }
+                                }
+                            })
+                        ],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 22,
+                            bytes: ByteSpan { start: 0, end: 21 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 22,
+                        bytes: ByteSpan { start: 0, end: 21 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_bold_inside_italic() {
+            let input = "*a **b** c*";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Italic(Italic {
+                        nodes: vec![
+                            Node::Text(Text {
+                                value: "a".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 2,
+                                    end_col: 3,
+                                    bytes: ByteSpan { start: 1, end: 2 }
+                                }
+                            }),
+                            Node::Whitespace(Whitespace {
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 3,
+                                    end_col: 4,
+                                    bytes: ByteSpan { start: 2, end: 3 }
+                                }
+                            }),
+                            Node::Bold(Bold {
+                                nodes: vec![Node::Text(Text {
+                                    value: "b".to_string(),
+                                    position: LineSpan {
+                                        start: 1,
+                                        end: 1,
+                                        start_col: 6,
+                                        end_col: 7,
+                                        bytes: ByteSpan { start: 5, end: 6 }
+                                    }
+                                })],
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 4,
+                                    end_col: 9,
+                                    bytes: ByteSpan { start: 3, end: 8 }
+                                }
+                            }),
+                            Node::Whitespace(Whitespace {
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 9,
+                                    end_col: 10,
+                                    bytes: ByteSpan { start: 8, end: 9 }
+                                }
+                            }),
+                            Node::Text(Text {
+                                value: "c".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 10,
+                                    end_col: 11,
+                                    bytes: ByteSpan { start: 9, end: 10 }
+                                }
+                            })
+                        ],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 12,
+                            bytes: ByteSpan { start: 0, end: 11 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 12,
+                        bytes: ByteSpan { start: 0, end: 11 }
+                    }
+                })]
+            )
+        }
+    }
+
+    mod inline_code_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_closed_inline_code() {
+            let input = "`let x = 1` code";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::InlineCode(InlineCode {
+                            value: "let x = 1".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 12,
+                                bytes: ByteSpan { start: 0, end: 11 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 12,
+                                end_col: 13,
+                                bytes: ByteSpan { start: 11, end: 12 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "code".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 13,
+                                end_col: 17,
+                                bytes: ByteSpan { start: 12, end: 16 }
+                            }
+                        }),
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 17,
+                        bytes: ByteSpan { start: 0, end: 16 }
+                    }
+                },)],
+            )
+        }
+
+        #[test]
+        fn test_unclosed_inline_code() {
+            let input = "`code *italic*";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "`".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 0, end: 1 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "code".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 2,
+                                end_col: 6,
+                                bytes: ByteSpan { start: 1, end: 5 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 6,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 5, end: 6 }
+                            }
+                        }),
+                        Node::Italic(Italic {
+                            nodes: vec![Node::Text(Text {
+                                value: "italic".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 8,
+                                    end_col: 14,
+                                    bytes: ByteSpan { start: 7, end: 13 }
+                                }
+                            }),],
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 7,
+                                end_col: 15,
+                                bytes: ByteSpan { start: 6, end: 14 }
+                            }
+                        }),
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 15,
+                        bytes: ByteSpan { start: 0, end: 14 }
+                    }
+                },)],
+            )
+        }
+
+        #[test]
+        fn test_emphasis_inside_inline_code() {
+            let input = "`*a* **b**`";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::InlineCode(InlineCode {
+                        value: "*a* **b**".to_string(),
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 12,
+                            bytes: ByteSpan { start: 0, end: 11 }
+                        }
+                    }),],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 12,
+                        bytes: ByteSpan { start: 0, end: 11 }
+                    }
+                },)],
+            )
+        }
+
+        #[test]
+        fn test_inline_code_strips_one_space_each_side() {
+            let input = "` x `";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::InlineCode(InlineCode {
+                        value: "x".to_string(),
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 6,
+                            bytes: ByteSpan { start: 0, end: 5 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 6,
+                        bytes: ByteSpan { start: 0, end: 5 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_inline_code_without_spaces() {
+            let input = "`x`";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::InlineCode(InlineCode {
+                        value: "x".to_string(),
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 4,
+                            bytes: ByteSpan { start: 0, end: 3 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 4,
+                        bytes: ByteSpan { start: 0, end: 3 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_inline_code_keeps_inner_spaces() {
+            let input = "`  x  `";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::InlineCode(InlineCode {
+                        value: " x ".to_string(),
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 8,
+                            bytes: ByteSpan { start: 0, end: 7 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 8,
+                        bytes: ByteSpan { start: 0, end: 7 }
+                    }
+                })]
+            )
+        }
+    }
+
+    mod link_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_link() {
+            let input = "[link](https://example.com)";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Link(Link {
+                        text: vec![Node::Text(Text {
+                            value: "link".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 2,
+                                end_col: 6,
+                                bytes: ByteSpan { start: 1, end: 5 }
+                            }
+                        })],
+                        url: "https://example.com".to_string(),
+                        title: None,
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 28,
+                            bytes: ByteSpan { start: 0, end: 27 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 28,
+                        bytes: ByteSpan { start: 0, end: 27 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_link_with_emphasized_text() {
+            let input = "[*docs* here](x \"Title\")";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Link(Link {
+                        text: vec![
+                            Node::Italic(Italic {
+                                nodes: vec![Node::Text(Text {
+                                    value: "docs".to_string(),
+                                    position: LineSpan {
+                                        start: 1,
+                                        end: 1,
+                                        start_col: 3,
+                                        end_col: 7,
+                                        bytes: ByteSpan { start: 2, end: 6 }
+                                    }
+                                })],
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 2,
+                                    end_col: 8,
+                                    bytes: ByteSpan { start: 1, end: 7 }
+                                }
+                            }),
+                            Node::Whitespace(Whitespace {
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 8,
+                                    end_col: 9,
+                                    bytes: ByteSpan { start: 7, end: 8 }
+                                }
+                            }),
+                            Node::Text(Text {
+                                value: "here".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 9,
+                                    end_col: 13,
+                                    bytes: ByteSpan { start: 8, end: 12 }
+                                }
+                            }),
+                        ],
+                        url: "x".to_string(),
+                        title: Some("Title".to_string()),
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 25,
+                            bytes: ByteSpan { start: 0, end: 24 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 25,
+                        bytes: ByteSpan { start: 0, end: 24 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_link_inside_italic() {
+            let input = "*see [docs](x)*";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Italic(Italic {
+                        nodes: vec![
+                            Node::Text(Text {
+                                value: "see".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 2,
+                                    end_col: 5,
+                                    bytes: ByteSpan { start: 1, end: 4 }
+                                }
+                            }),
+                            Node::Whitespace(Whitespace {
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 5,
+                                    end_col: 6,
+                                    bytes: ByteSpan { start: 4, end: 5 }
+                                }
+                            }),
+                            Node::Link(Link {
+                                text: vec![Node::Text(Text {
+                                    value: "docs".to_string(),
+                                    position: LineSpan {
+                                        start: 1,
+                                        end: 1,
+                                        start_col: 7,
+                                        end_col: 11,
+                                        bytes: ByteSpan { start: 6, end: 10 }
+                                    }
+                                })],
+                                url: "x".to_string(),
+                                title: None,
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 6,
+                                    end_col: 15,
+                                    bytes: ByteSpan { start: 5, end: 14 }
+                                }
+                            }),
+                        ],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 16,
+                            bytes: ByteSpan { start: 0, end: 15 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 16,
+                        bytes: ByteSpan { start: 0, end: 15 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_link_without_url() {
+            let input = "[text]";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "[".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 0, end: 1 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "text".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 2,
+                                end_col: 6,
+                                bytes: ByteSpan { start: 1, end: 5 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "]".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 6,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 5, end: 6 }
+                            }
+                        })
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 7,
+                        bytes: ByteSpan { start: 0, end: 6 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_reference_link() {
+            let input = "[docs][Ref]\n\n[ref]: https://example.com \"Title\"";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Link(Link {
+                            text: vec![Node::Text(Text {
+                                value: "docs".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 2,
+                                    end_col: 6,
+                                    bytes: ByteSpan { start: 1, end: 5 }
+                                }
+                            })],
+                            url: "https://example.com".to_string(),
+                            title: Some("Title".to_string()),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 12,
+                                bytes: ByteSpan { start: 0, end: 11 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 12,
+                            bytes: ByteSpan { start: 0, end: 11 }
+                        }
+                    }),
+                    Node::Eol(Eol {
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 2,
+                            bytes: ByteSpan { start: 12, end: 13 }
+                        }
+                    })
+                ]
+            )
+        }
+
+        #[test]
+        fn test_collapsed_reference_link() {
+            let input = "[ref]: https://example.com\n[Ref][]";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Link(Link {
+                        text: vec![Node::Text(Text {
+                            value: "Ref".to_string(),
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 2,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 28, end: 31 }
+                            }
+                        })],
+                        url: "https://example.com".to_string(),
+                        title: None,
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 8,
+                            bytes: ByteSpan { start: 27, end: 34 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 2,
+                        end: 2,
+                        start_col: 1,
+                        end_col: 8,
+                        bytes: ByteSpan { start: 27, end: 34 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_undefined_reference_link() {
+            let input = "[text][nope]";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "[".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 0, end: 1 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "text".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 2,
+                                end_col: 6,
+                                bytes: ByteSpan { start: 1, end: 5 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "]".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 6,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 5, end: 6 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "[".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 7,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 6, end: 7 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "nope".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 8,
+                                end_col: 12,
+                                bytes: ByteSpan { start: 7, end: 11 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "]".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 12,
+                                end_col: 13,
+                                bytes: ByteSpan { start: 11, end: 12 }
+                            }
+                        })
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 13,
+                        bytes: ByteSpan { start: 0, end: 12 }
+                    }
+                })]
+            )
+        }
+    }
+
+    mod image_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_image() {
+            let input = "![alt *text*](img.png)";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Image(Image {
+                        alt: "alt text".to_string(),
+                        src: "img.png".to_string(),
+                        title: None,
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 23,
+                            bytes: ByteSpan { start: 0, end: 22 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 23,
+                        bytes: ByteSpan { start: 0, end: 22 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_image_with_title() {
+            let input = "![a](b \"T\")";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Image(Image {
+                        alt: "a".to_string(),
+                        src: "b".to_string(),
+                        title: Some("T".to_string()),
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 12,
+                            bytes: ByteSpan { start: 0, end: 11 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 12,
+                        bytes: ByteSpan { start: 0, end: 11 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_bare_exclamation() {
+            let input = "wow !";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "wow".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 0, end: 3 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 4,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 3, end: 4 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "!".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 5,
+                                end_col: 6,
+                                bytes: ByteSpan { start: 4, end: 5 }
+                            }
+                        })
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 6,
+                        bytes: ByteSpan { start: 0, end: 5 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_image_inside_link() {
+            let input = "[![alt](img)](url)";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Link(Link {
+                        text: vec![Node::Image(Image {
+                            alt: "alt".to_string(),
+                            src: "img".to_string(),
+                            title: None,
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 2,
+                                end_col: 13,
+                                bytes: ByteSpan { start: 1, end: 12 }
+                            }
+                        })],
+                        url: "url".to_string(),
+                        title: None,
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 19,
+                            bytes: ByteSpan { start: 0, end: 18 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 19,
+                        bytes: ByteSpan { start: 0, end: 18 }
+                    }
+                })]
+            )
+        }
+    }
+
+    mod code_block_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_code_block_with_trailing_content_after_fences() {
+            let input = "```rust extra\nlet x = 1;\n``` code\n```  ";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::CodeBlock(CodeBlock {
+                    language: Some("rust extra".to_string()),
+                    value: "let x = 1;\n``` code".to_string(),
+                    position: LineSpan {
+                        start: 1,
+                        end: 4,
+                        start_col: 1,
+                        end_col: 4,
+                        bytes: ByteSpan { start: 0, end: 37 }
+                    }
+                })],
+            )
+        }
+
+        #[test]
+        fn test_code_block_with_language() {
+            let input = "```rust\nfn main() {\n    # not a header\n}\n```\ntext";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![
+                    Node::CodeBlock(CodeBlock {
+                        language: Some("rust".to_string()),
+                        value: "fn main() {\n    # not a header\n}".to_string(),
+                        position: LineSpan {
+                            start: 1,
+                            end: 5,
+                            start_col: 1,
+                            end_col: 4,
+                            bytes: ByteSpan { start: 0, end: 44 }
+                        }
+                    }),
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: "text".to_string(),
+                            position: LineSpan {
+                                start: 6,
+                                end: 6,
+                                start_col: 1,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 45, end: 49 }
+                            }
+                        }),],
+                        position: LineSpan {
+                            start: 6,
+                            end: 6,
+                            start_col: 1,
+                            end_col: 5,
+                            bytes: ByteSpan { start: 45, end: 49 }
+                        }
+                    }),
+                ],
+            )
+        }
+
+        #[test]
+        fn test_code_block_keeps_tabs() {
+            let input = "```\n\tif x {\n\t\treturn;\n\t}\n```";
+            let nodes = Parser::new().tab_width(2).parse(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::CodeBlock(CodeBlock {
+                    language: None,
+                    value: "\tif x {\n\t\treturn;\n\t}".to_string(),
+                    position: LineSpan {
+                        start: 1,
+                        end: 5,
+                        start_col: 1,
+                        end_col: 4,
+                        bytes: ByteSpan { start: 0, end: 28 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_empty_code_block() {
+            let input = "```\n```";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::CodeBlock(CodeBlock {
+                    language: None,
+                    value: "".to_string(),
+                    position: LineSpan {
+                        start: 1,
+                        end: 2,
+                        start_col: 1,
+                        end_col: 4,
+                        bytes: ByteSpan { start: 0, end: 7 }
+                    }
+                })],
+            )
+        }
+
+        #[test]
+        fn test_unterminated_code_block() {
+            let input = "```\n- item\n\n**bold**";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::CodeBlock(CodeBlock {
+                    language: None,
+                    value: "- item\n\n**bold**".to_string(),
+                    position: LineSpan {
+                        start: 1,
+                        end: 4,
+                        start_col: 1,
+                        end_col: 9,
+                        bytes: ByteSpan { start: 0, end: 20 }
+                    }
+                })],
+            )
+        }
+
+        #[test]
+        fn test_unterminated_code_block_with_language() {
+            let input = "```rust\nlet x = 1;";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::CodeBlock(CodeBlock {
+                    language: Some("rust".to_string()),
+                    value: "let x = 1;".to_string(),
+                    position: LineSpan {
+                        start: 1,
+                        end: 2,
+                        start_col: 1,
+                        end_col: 11,
+                        bytes: ByteSpan { start: 0, end: 18 }
+                    }
+                })],
+            )
+        }
+
+        #[test]
+        fn test_escaped_code_fence() {
+            let input = "\\```not code";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "```".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 0, end: 4 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "not".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 5,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 4, end: 7 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 8,
+                                end_col: 9,
+                                bytes: ByteSpan { start: 7, end: 8 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "code".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 9,
+                                end_col: 13,
+                                bytes: ByteSpan { start: 8, end: 12 }
+                            }
+                        })
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 13,
+                        bytes: ByteSpan { start: 0, end: 12 }
+                    }
+                })]
+            )
+        }
+    }
+
+    mod parser_option_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_max_header_level() {
+            let parser = Parser::new().max_header_level(3);
+
+            assert_eq!(
+                parser.parse("### Header"),
+                vec![Node::Header(Header {
+                    level: 3,
+                    nodes: vec![Node::Text(Text {
+                        value: "Header".to_string(),
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 5,
+                            end_col: 11,
+                            bytes: ByteSpan { start: 4, end: 10 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 11,
+                        bytes: ByteSpan { start: 0, end: 10 }
+                    }
+                })],
+            );
+            assert_eq!(
+                parser.parse("#### Header"),
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "####".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 0, end: 4 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 5,
+                                end_col: 6,
+                                bytes: ByteSpan { start: 4, end: 5 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "Header".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 6,
+                                end_col: 12,
+                                bytes: ByteSpan { start: 5, end: 11 }
+                            }
+                        }),
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 12,
+                        bytes: ByteSpan { start: 0, end: 11 }
+                    }
+                })],
+            );
+        }
+
+        #[test]
+        fn test_strict_emphasis() {
+            let parser = Parser::new().strict_emphasis(true);
+
+            assert_eq!(
+                parser.parse("**unclosed"),
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![Node::Text(Text {
+                        value: "unclosed".to_string(),
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 3,
+                            end_col: 11,
+                            bytes: ByteSpan { start: 2, end: 10 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 3,
+                        end_col: 11,
+                        bytes: ByteSpan { start: 2, end: 10 }
+                    }
+                })],
+            );
+        }
+
+        #[test]
+        fn test_table_of_contents() {
+            let parser = Parser::new().table_of_contents(true);
+            let nodes = parser.parse("[TOC]\n# Intro\n## Setup *steps*\n## Setup steps");
+
+            assert_eq!(
+                nodes[0],
+                Node::TableOfContents(TableOfContents {
+                    entries: vec![
+                        HeadingEntry {
+                            level: 1,
+                            text: "Intro".to_string(),
+                            anchor: "intro".to_string(),
+                        },
+                        HeadingEntry {
+                            level: 2,
+                            text: "Setup steps".to_string(),
+                            anchor: "setup-steps".to_string(),
+                        },
+                        HeadingEntry {
+                            level: 2,
+                            text: "Setup steps".to_string(),
+                            anchor: "setup-steps-1".to_string(),
+                        },
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 6,
+                        bytes: ByteSpan { start: 0, end: 5 }
+                    }
+                })
+            );
+            assert_eq!(nodes.len(), 4);
+        }
+
+        #[test]
+        fn test_ordered_lists_disabled() {
+            let parser = Parser::new().ordered_lists(false);
+
+            assert_eq!(
+                parser.parse("1. text"),
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "1.".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 3,
+                                bytes: ByteSpan { start: 0, end: 2 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 2, end: 3 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "text".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 4,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 3, end: 7 }
+                            }
+                        })
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 8,
+                        bytes: ByteSpan { start: 0, end: 7 }
+                    }
+                })]
+            );
+        }
+
+        #[test]
+        fn test_spaces_per_level() {
+            let parser = Parser::new();
+            let one_space = clear_positions(parser.parse("- item\n - nested\n  - deeper\n- next"));
+
+            let two_spaces = "- item\n  - nested\n    - deeper\n- next";
+            let nodes = parser.clone().spaces_per_level(2).parse(two_spaces);
+            assert_eq!(clear_positions(nodes), one_space);
+
+            let four_spaces = "- item\n    - nested\n        - deeper\n- next";
+            let nodes = parser.clone().spaces_per_level(4).parse(four_spaces);
+            assert_eq!(clear_positions(nodes), one_space);
+        }
+
+        #[test]
+        fn test_tab_width_positions() {
+            let nodes = Parser::new().parse("\ta\tb");
+            let Node::Paragraph(paragraph) = &nodes[0] else {
+                panic!("expected a paragraph, got {:?}", nodes[0]);
+            };
+            let columns: Vec<_> = paragraph
+                .nodes
+                .iter()
+                .map(|node| (node.position().start_col, node.position().end_col))
+                .collect();
+            // The leading tab ends at column 5, and the one after `a` at the next stop
+            assert_eq!(columns, vec![(1, 5), (5, 6), (6, 9), (9, 10)]);
+
+            let nodes = Parser::new().tab_width(2).parse("\ta\tb");
+            let Node::Paragraph(paragraph) = &nodes[0] else {
+                panic!("expected a paragraph, got {:?}", nodes[0]);
+            };
+            let whitespace = paragraph.nodes[2].position();
+            assert_eq!((whitespace.start_col, whitespace.end_col), (4, 5));
+        }
+
+        #[test]
+        fn test_spaces_per_level_nests_one_level() {
+            let parser = Parser::new();
+            let two_spaces = clear_positions(parser.parse("- item\n  - nested"));
+            let Node::UnorderedList(list) = &two_spaces[0] else {
+                panic!("expected a list item, got {:?}", two_spaces[0]);
+            };
+            let Node::UnorderedList(child) = &list.children[0] else {
+                panic!("expected a nested item, got {:?}", list.children[0]);
+            };
+            assert_eq!(child.level, 1);
+
+            let three_spaces = parser.parse("- item\n   - nested");
+            assert_eq!(clear_positions(three_spaces), two_spaces);
+
+            let four_spaces = parser.parse("- item\n    - nested");
+            assert_eq!(clear_positions(four_spaces), two_spaces);
+        }
+
+        #[test]
+        fn test_indented_siblings() {
+            let parser = Parser::new();
+            let texts = |list: &UnorderedList| -> Vec<String> {
+                list.children
+                    .iter()
+                    .map(|child| {
+                        let Node::UnorderedList(child) = child else {
+                            panic!("expected a nested item, got {:?}", child);
+                        };
+                        assert_eq!(child.level, 1);
+                        assert!(child.children.is_empty());
+                        crate::tree::plain_text(&child.nodes)
+                    })
+                    .collect()
+            };
+
+            for input in ["- a\n  - b\n  - c\n  - d", "- a\n    - b\n    - c\n    - d"] {
+                for spaces in [1, 2] {
+                    let nodes = parser.clone().spaces_per_level(spaces).parse(input);
+                    assert_eq!(nodes.len(), 1, "{input:?} with {spaces} spaces per level");
+                    let Node::UnorderedList(list) = &nodes[0] else {
+                        panic!("expected a list item, got {:?}", nodes[0]);
+                    };
+                    assert_eq!(texts(list), vec!["b", "c", "d"]);
+                }
+            }
+        }
+
+        #[test]
+        fn test_list_indent() {
+            let input = "- item\n  not enough\n- next\n    deep enough";
+            let nodes = Parser::new().list_indent(4).parse(input);
+
+            assert_eq!(
+                nodes,
+                vec![
+                    Node::UnorderedList(UnorderedList {
+                        level: 0,
+                        checked: None,
+                        nodes: vec![Node::Text(Text {
+                            value: "item".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 2, end: 6 }
+                            }
+                        })],
+                        children: vec![],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 7,
+                            bytes: ByteSpan { start: 0, end: 6 }
+                        }
+                    }),
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![
+                            Node::Whitespace(Whitespace {
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 1,
+                                    end_col: 3,
+                                    bytes: ByteSpan { start: 7, end: 9 }
+                                }
+                            }),
+                            Node::Text(Text {
+                                value: "not".to_string(),
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 3,
+                                    end_col: 6,
+                                    bytes: ByteSpan { start: 9, end: 12 }
+                                }
+                            }),
+                            Node::Whitespace(Whitespace {
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 6,
+                                    end_col: 7,
+                                    bytes: ByteSpan { start: 12, end: 13 }
+                                }
+                            }),
+                            Node::Text(Text {
+                                value: "enough".to_string(),
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 7,
+                                    end_col: 13,
+                                    bytes: ByteSpan { start: 13, end: 19 }
+                                }
+                            })
+                        ],
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 13,
+                            bytes: ByteSpan { start: 7, end: 19 }
+                        }
+                    }),
+                    Node::UnorderedList(UnorderedList {
+                        level: 0,
+                        checked: None,
+                        nodes: vec![
+                            Node::Text(Text {
+                                value: "next".to_string(),
+                                position: LineSpan {
+                                    start: 3,
+                                    end: 3,
+                                    start_col: 3,
+                                    end_col: 7,
+                                    bytes: ByteSpan { start: 22, end: 26 }
+                                }
+                            }),
+                            Node::Paragraph(Paragraph {
+                                nodes: vec![
+                                    Node::Text(Text {
+                                        value: "deep".to_string(),
+                                        position: LineSpan {
+                                            start: 4,
+                                            end: 4,
+                                            start_col: 5,
+                                            end_col: 9,
+                                            bytes: ByteSpan { start: 31, end: 35 }
+                                        }
+                                    }),
+                                    Node::Whitespace(Whitespace {
+                                        position: LineSpan {
+                                            start: 4,
+                                            end: 4,
+                                            start_col: 9,
+                                            end_col: 10,
+                                            bytes: ByteSpan { start: 35, end: 36 }
+                                        }
+                                    }),
+                                    Node::Text(Text {
+                                        value: "enough".to_string(),
+                                        position: LineSpan {
+                                            start: 4,
+                                            end: 4,
+                                            start_col: 10,
+                                            end_col: 16,
+                                            bytes: ByteSpan { start: 36, end: 42 }
+                                        }
+                                    })
+                                ],
+                                position: LineSpan {
+                                    start: 4,
+                                    end: 4,
+                                    start_col: 5,
+                                    end_col: 16,
+                                    bytes: ByteSpan { start: 31, end: 42 }
+                                }
+                            })
+                        ],
+                        children: vec![],
+                        position: LineSpan {
+                            start: 3,
+                            end: 4,
+                            start_col: 1,
+                            end_col: 16,
+                            bytes: ByteSpan { start: 20, end: 42 }
+                        }
+                    })
+                ]
+            )
+        }
+    }
+
+    mod grouped_list_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_sibling_items() {
+            let input = "- a\n- b\n- c";
+
+            assert_eq!(
+                build_tree_grouped(input),
+                vec![Node::List(List {
+                    ordered: false,
+                    start: 1,
+                    loose: false,
+                    items: vec![
+                        Node::ListItem(ListItem {
+                            checked: None,
+                            nodes: vec![Node::Text(Text {
+                                value: "a".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 3,
+                                    end_col: 4,
+                                    bytes: ByteSpan { start: 2, end: 3 }
+                                }
+                            })],
+                            children: vec![],
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 0, end: 3 }
+                            }
+                        }),
+                        Node::ListItem(ListItem {
+                            checked: None,
+                            nodes: vec![Node::Text(Text {
+                                value: "b".to_string(),
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 3,
+                                    end_col: 4,
+                                    bytes: ByteSpan { start: 6, end: 7 }
+                                }
+                            })],
+                            children: vec![],
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 1,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 4, end: 7 }
+                            }
+                        }),
+                        Node::ListItem(ListItem {
+                            checked: None,
+                            nodes: vec![Node::Text(Text {
+                                value: "c".to_string(),
+                                position: LineSpan {
+                                    start: 3,
+                                    end: 3,
+                                    start_col: 3,
+                                    end_col: 4,
+                                    bytes: ByteSpan { start: 10, end: 11 }
+                                }
+                            })],
+                            children: vec![],
+                            position: LineSpan {
+                                start: 3,
+                                end: 3,
+                                start_col: 1,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 8, end: 11 }
+                            }
+                        })
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 3,
+                        start_col: 1,
+                        end_col: 4,
+                        bytes: ByteSpan { start: 0, end: 11 }
+                    }
+                })]
+            );
+        }
+
+        #[test]
+        fn test_nested_item() {
+            let input = "- a\n - a.1\n- b";
+
+            assert_eq!(
+                build_tree_grouped(input),
+                vec![Node::List(List {
+                    ordered: false,
+                    start: 1,
+                    loose: false,
+                    items: vec![
+                        Node::ListItem(ListItem {
+                            checked: None,
+                            nodes: vec![Node::Text(Text {
+                                value: "a".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 3,
+                                    end_col: 4,
+                                    bytes: ByteSpan { start: 2, end: 3 }
+                                }
+                            })],
+                            children: vec![Node::List(List {
+                                ordered: false,
+                                start: 1,
+                                loose: false,
+                                items: vec![Node::ListItem(ListItem {
+                                    checked: None,
+                                    nodes: vec![Node::Text(Text {
+                                        value: "a.1".to_string(),
+                                        position: LineSpan {
+                                            start: 2,
+                                            end: 2,
+                                            start_col: 4,
+                                            end_col: 7,
+                                            bytes: ByteSpan { start: 7, end: 10 }
+                                        }
+                                    })],
+                                    children: vec![],
+                                    position: LineSpan {
+                                        start: 2,
+                                        end: 2,
+                                        start_col: 2,
+                                        end_col: 7,
+                                        bytes: ByteSpan { start: 5, end: 10 }
+                                    }
+                                })],
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 2,
+                                    end_col: 7,
+                                    bytes: ByteSpan { start: 5, end: 10 }
+                                }
+                            })],
+                            position: LineSpan {
+                                start: 1,
+                                end: 2,
+                                start_col: 1,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 0, end: 10 }
+                            }
+                        }),
+                        Node::ListItem(ListItem {
+                            checked: None,
+                            nodes: vec![Node::Text(Text {
+                                value: "b".to_string(),
+                                position: LineSpan {
+                                    start: 3,
+                                    end: 3,
+                                    start_col: 3,
+                                    end_col: 4,
+                                    bytes: ByteSpan { start: 13, end: 14 }
+                                }
+                            })],
+                            children: vec![],
+                            position: LineSpan {
+                                start: 3,
+                                end: 3,
+                                start_col: 1,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 11, end: 14 }
+                            }
+                        })
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 3,
+                        start_col: 1,
+                        end_col: 4,
+                        bytes: ByteSpan { start: 0, end: 14 }
+                    }
+                })]
+            );
+        }
+
+        #[test]
+        fn test_loose_list() {
+            let is_loose = |input| match &build_tree_grouped(input)[0] {
+                Node::List(list) => list.loose,
+                _ => panic!("expected a list"),
+            };
+
+            assert!(is_loose("- a\n\n  more text\n- b"));
+            assert!(!is_loose("- a\n- b\n - c"));
+        }
+    }
+
+    mod diagnostics_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_unclosed_emphasis_diagnostic() {
+            let (_, diagnostics) = build_tree_with_diagnostics("text\n*unclosed");
+
+            assert_eq!(
+                diagnostics,
+                vec![Diagnostic {
+                    message: "unclosed emphasis marker `*`".to_string(),
+                    line: 2,
+                    severity: Severity::Warning,
+                }]
+            );
+        }
+
+        #[test]
+        fn test_unclosed_code_block_diagnostic() {
+            let (nodes, diagnostics) = build_tree_with_diagnostics("```rust\nlet x = 1;");
+
+            assert_eq!(nodes, build_tree("```rust\nlet x = 1;"));
+            assert_eq!(
+                diagnostics,
+                vec![Diagnostic {
+                    message: "unclosed code block".to_string(),
+                    line: 1,
+                    severity: Severity::Warning,
+                }]
+            );
+        }
+
+        #[test]
+        fn test_too_deep_header_diagnostic() {
+            let (nodes, diagnostics) = build_tree_with_diagnostics("####### too deep");
+
+            assert_eq!(nodes, build_tree("####### too deep"));
+            assert_eq!(
+                diagnostics,
+                vec![Diagnostic {
+                    message: "header level 7 exceeds the maximum of 6".to_string(),
+                    line: 1,
+                    severity: Severity::Warning,
+                }]
+            );
+        }
+
+        #[test]
+        fn test_unknown_character_diagnostic() {
+            let (nodes, diagnostics) = build_tree_with_diagnostics("# Title\nbell\u{7}");
+
+            assert_eq!(
+                nodes[1],
+                Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "bell".to_string(),
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 1,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 8, end: 12 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "\u{7}".to_string(),
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 5,
+                                end_col: 6,
+                                bytes: ByteSpan { start: 12, end: 13 }
+                            }
+                        }),
+                    ],
+                    position: LineSpan {
+                        start: 2,
+                        end: 2,
+                        start_col: 1,
+                        end_col: 6,
+                        bytes: ByteSpan { start: 8, end: 13 }
+                    }
+                })
+            );
+            assert_eq!(
+                diagnostics,
+                vec![Diagnostic {
+                    message: "unknown character \"\\u{7}\"".to_string(),
+                    line: 2,
+                    severity: Severity::Warning,
+                }]
+            );
+        }
+
+        #[test]
+        fn test_no_diagnostics() {
+            let (_, diagnostics) = build_tree_with_diagnostics("# Title\n**bold** and *italic*");
+
+            assert_eq!(diagnostics, vec![]);
+        }
+    }
+
+    mod parse_iter_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_parse_iter_matches_build_tree() {
+            let input = "# Title\ntext\n\n- item\n - nested\n```rust\ncode\n```";
+
+            assert_eq!(parse_iter(input).collect::<Vec<_>>(), build_tree(input));
+        }
+
+        #[test]
+        fn test_parse_iter_takes_first_block() {
+            let input = format!(
+                "# Title\n{}```unterminated\n*unclosed",
+                "- item\n".repeat(10_000)
+            );
+
+            assert_eq!(
+                parse_iter(&input).next(),
+                Some(Node::Header(Header {
+                    level: 1,
+                    nodes: vec![Node::Text(Text {
+                        value: "Title".to_string(),
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 3,
+                            end_col: 8,
+                            bytes: ByteSpan { start: 2, end: 7 }
+                        }
+                    })],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 8,
+                        bytes: ByteSpan { start: 0, end: 7 }
+                    }
+                }))
+            );
+        }
+
+        #[test]
+        fn test_parse_iter_leaves_rest_unparsed() {
+            let input = format!("# Title\n{}", "- item\n".repeat(10_000));
+            let mut blocks = block_iter(&input);
+            blocks.next();
+
+            // Only the header line has been consumed
+            assert_eq!(blocks.index, 4);
+            assert!(blocks.tokens.len() > 10_000);
+        }
+    }
+
+    mod metrics_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_build_tree_with_metrics() {
+            let input = "# Title\n- item\n - nested";
+            let (nodes, metrics) = build_tree_with_metrics(input);
+
+            assert_eq!(nodes, build_tree(input));
+            assert_eq!(metrics.token_count, lex(input).len());
+            assert_eq!(metrics.node_count, 6);
+            assert_eq!(metrics.max_depth, 3);
+        }
+    }
+
+    mod byte_span_tests {
+        use super::*;
+        use crate::tree::{walk_with_depth, NodeKind};
+        use pretty_assertions::assert_eq;
+
+        /// Returns the source text of each node of the given kind.
+        fn sources(input: &str, kind: NodeKind) -> Vec<&str> {
+            let mut sources = vec![];
+            walk_with_depth(&build_tree(input), |_, node| {
+                if node.kind() == kind {
+                    let bytes = &node.position().bytes;
+                    sources.push(&input[bytes.range()]);
+                }
+            });
+            sources
+        }
+
+        #[test]
+        fn test_byte_spans_of_multibyte_text() {
+            let input = "# 見出し *強調*\n- 項目 one\n2. 二つ";
+
+            assert_eq!(sources(input, NodeKind::Header), vec!["# 見出し *強調*"]);
+            assert_eq!(sources(input, NodeKind::Italic), vec!["*強調*"]);
+            assert_eq!(
+                sources(input, NodeKind::Text),
+                vec!["見出し", "強調", "項目", "one", "二つ"]
+            );
+            assert_eq!(sources(input, NodeKind::OrderedList), vec!["2. 二つ"]);
+        }
+
+        #[test]
+        fn test_byte_span_of_unclosed_emphasis_marker() {
+            let input = "テキスト **強調";
+
+            assert_eq!(
+                sources(input, NodeKind::Text),
+                vec!["テキスト", "**", "強調"]
+            );
+        }
+    }
+
+    mod paragraph_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_plain_text() {
+            let input = "normal text";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "normal".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 0, end: 6 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 7,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 6, end: 7 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "text".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 8,
+                                end_col: 12,
+                                bytes: ByteSpan { start: 7, end: 11 }
+                            }
+                        }),
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 12,
+                        bytes: ByteSpan { start: 0, end: 11 }
+                    }
                 },)],
             )
         }
 
         #[test]
-        fn test_multiple_text() {
-            let input = "**bold**\n*italic*\nplain";
+        fn test_break() {
+            let input = "normal\n\ntext";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: "normal".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 0, end: 6 }
+                            }
+                        }),],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 7,
+                            bytes: ByteSpan { start: 0, end: 6 }
+                        }
+                    },),
+                    Node::Eol(Eol {
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 2,
+                            bytes: ByteSpan { start: 7, end: 8 }
+                        }
+                    }),
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: "text".to_string(),
+                            position: LineSpan {
+                                start: 3,
+                                end: 3,
+                                start_col: 1,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 8, end: 12 }
+                            }
+                        }),],
+                        position: LineSpan {
+                            start: 3,
+                            end: 3,
+                            start_col: 1,
+                            end_col: 5,
+                            bytes: ByteSpan { start: 8, end: 12 }
+                        }
+                    },),
+                ],
+            )
+        }
+
+        #[test]
+        fn test_hard_break_with_spaces() {
+            let input = "a  \nb";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "a".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 0, end: 1 }
+                            }
+                        }),
+                        Node::HardBreak(HardBreak {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 2,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 1, end: 3 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "b".to_string(),
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 4, end: 5 }
+                            }
+                        })
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 2,
+                        start_col: 1,
+                        end_col: 2,
+                        bytes: ByteSpan { start: 0, end: 5 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_hard_break_with_backslash() {
+            let input = "a\\\nb";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![Node::Paragraph(Paragraph {
+                    nodes: vec![
+                        Node::Text(Text {
+                            value: "a".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 0, end: 1 }
+                            }
+                        }),
+                        Node::HardBreak(HardBreak {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 2,
+                                end_col: 3,
+                                bytes: ByteSpan { start: 1, end: 2 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "b".to_string(),
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 3, end: 4 }
+                            }
+                        })
+                    ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 2,
+                        start_col: 1,
+                        end_col: 2,
+                        bytes: ByteSpan { start: 0, end: 4 }
+                    }
+                })]
+            )
+        }
+
+        #[test]
+        fn test_line_break_without_marker() {
+            // Without a hard break marker, each line is its own paragraph
+            let input = "a\nb";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: "a".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 0, end: 1 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 2,
+                            bytes: ByteSpan { start: 0, end: 1 }
+                        }
+                    }),
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: "b".to_string(),
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 2, end: 3 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 2,
+                            bytes: ByteSpan { start: 2, end: 3 }
+                        }
+                    })
+                ]
+            )
+        }
+    }
+
+    mod blank_input_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_empty_input() {
+            assert_eq!(build_tree(""), vec![]);
+        }
+
+        #[test]
+        fn test_whitespace_only_input() {
+            assert_eq!(build_tree("   "), vec![]);
+        }
+
+        #[test]
+        fn test_single_newline() {
+            assert_eq!(
+                build_tree("\n"),
+                vec![Node::Eol(Eol {
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 2,
+                        bytes: ByteSpan { start: 0, end: 1 }
+                    }
+                })]
+            );
+        }
+
+        #[test]
+        fn test_newlines_only() {
+            assert_eq!(
+                build_tree("\n\n\n"),
+                vec![
+                    Node::Eol(Eol {
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 2,
+                            bytes: ByteSpan { start: 0, end: 1 }
+                        }
+                    }),
+                    Node::Eol(Eol {
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 2,
+                            bytes: ByteSpan { start: 1, end: 2 }
+                        }
+                    }),
+                    Node::Eol(Eol {
+                        position: LineSpan {
+                            start: 3,
+                            end: 3,
+                            start_col: 1,
+                            end_col: 2,
+                            bytes: ByteSpan { start: 2, end: 3 }
+                        }
+                    })
+                ]
+            );
+        }
+
+        #[test]
+        fn test_whitespace_only_line() {
+            assert_eq!(
+                build_tree("   \n"),
+                vec![Node::Eol(Eol {
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 4,
+                        end_col: 5,
+                        bytes: ByteSpan { start: 3, end: 4 }
+                    }
+                })]
+            );
+        }
+    }
+
+    mod front_matter_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_front_matter() {
+            let input = "---\ntitle: Hello\ntags: [a, b]\n---\n# Title";
+            let nodes = build_tree(input);
+
+            assert_eq!(
+                nodes,
+                vec![
+                    Node::FrontMatter(FrontMatter {
+                        raw: "title: Hello\ntags: [a, b]".to_string(),
+                        position: LineSpan {
+                            start: 1,
+                            end: 4,
+                            start_col: 1,
+                            end_col: 4,
+                            bytes: ByteSpan { start: 0, end: 33 }
+                        }
+                    }),
+                    Node::Header(Header {
+                        level: 1,
+                        nodes: vec![Node::Text(Text {
+                            value: "Title".to_string(),
+                            position: LineSpan {
+                                start: 5,
+                                end: 5,
+                                start_col: 3,
+                                end_col: 8,
+                                bytes: ByteSpan { start: 36, end: 41 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 5,
+                            end: 5,
+                            start_col: 1,
+                            end_col: 8,
+                            bytes: ByteSpan { start: 34, end: 41 }
+                        }
+                    })
+                ]
+            )
+        }
+
+        #[test]
+        fn test_unclosed_front_matter() {
+            let input = "---\ntitle: x";
             let nodes = build_tree(input);
 
             assert_eq!(
                 nodes,
                 vec![
                     Node::Paragraph(Paragraph {
-                        nodes: vec![Node::Bold(Bold {
-                            nodes: vec![Node::Text(Text {
-                                value: "bold".to_string(),
-                                position: LineSpan { start: 1, end: 1 }
-                            }),],
-                            position: LineSpan { start: 1, end: 1 }
-                        })],
-                        position: LineSpan { start: 1, end: 1 }
-                    },),
-                    Node::Paragraph(Paragraph {
-                        nodes: vec![Node::Italic(Italic {
-                            nodes: vec![Node::Text(Text {
-                                value: "italic".to_string(),
-                                position: LineSpan { start: 2, end: 2 }
-                            }),],
-                            position: LineSpan { start: 2, end: 2 }
+                        nodes: vec![Node::Text(Text {
+                            value: "---".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 0, end: 3 }
+                            }
                         })],
-                        position: LineSpan { start: 2, end: 2 }
-                    },),
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 4,
+                            bytes: ByteSpan { start: 0, end: 3 }
+                        }
+                    }),
                     Node::Paragraph(Paragraph {
-                        nodes: vec![Node::Text(Text {
-                            value: "plain".to_string(),
-                            position: LineSpan { start: 3, end: 3 }
-                        }),],
-                        position: LineSpan { start: 3, end: 3 }
-                    },)
-                ],
+                        nodes: vec![
+                            Node::Text(Text {
+                                value: "title:".to_string(),
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 1,
+                                    end_col: 7,
+                                    bytes: ByteSpan { start: 4, end: 10 }
+                                }
+                            }),
+                            Node::Whitespace(Whitespace {
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 7,
+                                    end_col: 8,
+                                    bytes: ByteSpan { start: 10, end: 11 }
+                                }
+                            }),
+                            Node::Text(Text {
+                                value: "x".to_string(),
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 8,
+                                    end_col: 9,
+                                    bytes: ByteSpan { start: 11, end: 12 }
+                                }
+                            })
+                        ],
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 9,
+                            bytes: ByteSpan { start: 4, end: 12 }
+                        }
+                    })
+                ]
             )
         }
 
         #[test]
-        fn test_closed_italic_marker() {
-            let input = "*italic text*";
+        fn test_fence_later_in_document() {
+            let input = "text\n\n---\nb\n---";
             let nodes = build_tree(input);
 
             assert_eq!(
                 nodes,
-                vec![Node::Paragraph(Paragraph {
-                    nodes: vec![Node::Italic(Italic {
+                vec![
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: "text".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 5,
+                                bytes: ByteSpan { start: 0, end: 4 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 5,
+                            bytes: ByteSpan { start: 0, end: 4 }
+                        }
+                    }),
+                    Node::Eol(Eol {
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 2,
+                            bytes: ByteSpan { start: 5, end: 6 }
+                        }
+                    }),
+                    Node::Header(Header {
+                        level: 2,
                         nodes: vec![
                             Node::Text(Text {
-                                value: "italic".to_string(),
-                                position: LineSpan { start: 1, end: 1 }
+                                value: "---".to_string(),
+                                position: LineSpan {
+                                    start: 3,
+                                    end: 3,
+                                    start_col: 1,
+                                    end_col: 4,
+                                    bytes: ByteSpan { start: 6, end: 9 }
+                                }
                             }),
-                            Node::Whitespace(Whitespace {
-                                position: LineSpan { start: 1, end: 1 }
+                            Node::Eol(Eol {
+                                position: LineSpan {
+                                    start: 3,
+                                    end: 3,
+                                    start_col: 4,
+                                    end_col: 5,
+                                    bytes: ByteSpan { start: 9, end: 10 }
+                                }
                             }),
                             Node::Text(Text {
-                                value: "text".to_string(),
-                                position: LineSpan { start: 1, end: 1 }
-                            }),
+                                value: "b".to_string(),
+                                position: LineSpan {
+                                    start: 4,
+                                    end: 4,
+                                    start_col: 1,
+                                    end_col: 2,
+                                    bytes: ByteSpan { start: 10, end: 11 }
+                                }
+                            })
                         ],
-                        position: LineSpan { start: 1, end: 1 }
-                    })],
-                    position: LineSpan { start: 1, end: 1 }
-                },)],
+                        position: LineSpan {
+                            start: 3,
+                            end: 5,
+                            start_col: 1,
+                            end_col: 4,
+                            bytes: ByteSpan { start: 6, end: 15 }
+                        }
+                    })
+                ]
             )
         }
+    }
+
+    mod table_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
 
         #[test]
-        fn test_unclosed_italic_marker() {
-            let input = "*italic text";
+        fn test_table_with_alignments() {
+            let input = "| Name | Score |\n| :--- | ---: |\n| a | *1* |";
             let nodes = build_tree(input);
 
             assert_eq!(
                 nodes,
-                vec![Node::Paragraph(Paragraph {
-                    nodes: vec![
-                        Node::Text(Text {
-                            value: "*".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
-                        }),
-                        Node::Text(Text {
-                            value: "italic".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
-                        }),
-                        Node::Whitespace(Whitespace {
-                            position: LineSpan { start: 1, end: 1 }
-                        }),
-                        Node::Text(Text {
-                            value: "text".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
-                        }),
+                vec![Node::Table(Table {
+                    headers: vec![
+                        vec![Node::Text(Text {
+                            value: "Name".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 7,
+                                bytes: ByteSpan { start: 2, end: 6 }
+                            }
+                        })],
+                        vec![Node::Text(Text {
+                            value: "Score".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 10,
+                                end_col: 15,
+                                bytes: ByteSpan { start: 9, end: 14 }
+                            }
+                        })]
                     ],
-                    position: LineSpan { start: 1, end: 1 }
-                },)],
+                    alignments: vec![Alignment::Left, Alignment::Right],
+                    rows: vec![vec![
+                        vec![Node::Text(Text {
+                            value: "a".to_string(),
+                            position: LineSpan {
+                                start: 3,
+                                end: 3,
+                                start_col: 3,
+                                end_col: 4,
+                                bytes: ByteSpan { start: 35, end: 36 }
+                            }
+                        })],
+                        vec![Node::Italic(Italic {
+                            nodes: vec![Node::Text(Text {
+                                value: "1".to_string(),
+                                position: LineSpan {
+                                    start: 3,
+                                    end: 3,
+                                    start_col: 8,
+                                    end_col: 9,
+                                    bytes: ByteSpan { start: 40, end: 41 }
+                                }
+                            })],
+                            position: LineSpan {
+                                start: 3,
+                                end: 3,
+                                start_col: 7,
+                                end_col: 10,
+                                bytes: ByteSpan { start: 39, end: 42 }
+                            }
+                        })]
+                    ]],
+                    position: LineSpan {
+                        start: 1,
+                        end: 3,
+                        start_col: 1,
+                        end_col: 12,
+                        bytes: ByteSpan { start: 0, end: 44 }
+                    }
+                })]
             )
         }
 
         #[test]
-        fn test_unmatched_italic_marker() {
-            let input = "italic text*";
+        fn test_pipe_line_without_delimiter_row() {
+            let input = "a | b\nc";
             let nodes = build_tree(input);
 
             assert_eq!(
                 nodes,
-                vec![Node::Paragraph(Paragraph {
-                    nodes: vec![
-                        Node::Text(Text {
-                            value: "italic".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
-                        }),
-                        Node::Whitespace(Whitespace {
-                            position: LineSpan { start: 1, end: 1 }
-                        }),
-                        Node::Text(Text {
-                            value: "text".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
-                        }),
-                        Node::Text(Text {
-                            value: "*".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
-                        }),
-                    ],
-                    position: LineSpan { start: 1, end: 1 }
-                },)],
+                vec![
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![
+                            Node::Text(Text {
+                                value: "a".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 1,
+                                    end_col: 2,
+                                    bytes: ByteSpan { start: 0, end: 1 }
+                                }
+                            }),
+                            Node::Whitespace(Whitespace {
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 2,
+                                    end_col: 3,
+                                    bytes: ByteSpan { start: 1, end: 2 }
+                                }
+                            }),
+                            Node::Text(Text {
+                                value: "|".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 3,
+                                    end_col: 4,
+                                    bytes: ByteSpan { start: 2, end: 3 }
+                                }
+                            }),
+                            Node::Whitespace(Whitespace {
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 4,
+                                    end_col: 5,
+                                    bytes: ByteSpan { start: 3, end: 4 }
+                                }
+                            }),
+                            Node::Text(Text {
+                                value: "b".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 5,
+                                    end_col: 6,
+                                    bytes: ByteSpan { start: 4, end: 5 }
+                                }
+                            })
+                        ],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 6,
+                            bytes: ByteSpan { start: 0, end: 5 }
+                        }
+                    }),
+                    Node::Paragraph(Paragraph {
+                        nodes: vec![Node::Text(Text {
+                            value: "c".to_string(),
+                            position: LineSpan {
+                                start: 2,
+                                end: 2,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 6, end: 7 }
+                            }
+                        })],
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 2,
+                            bytes: ByteSpan { start: 6, end: 7 }
+                        }
+                    })
+                ]
             )
         }
 
         #[test]
-        fn test_closed_bold_marker() {
-            let input = "**bold text**";
+        fn test_table_rows_match_header_cells() {
+            let input = "a | b\n--|--\n1 |\n1 | 2 | 3\n\nafter";
+            let nodes = Parser::new().parse(input);
+
+            let Node::Table(table) = &nodes[0] else {
+                panic!("expected a table, got {:?}", nodes[0]);
+            };
+            assert_eq!(table.alignments, vec![Alignment::None, Alignment::None]);
+            let row_lengths: Vec<usize> = table.rows.iter().map(|row| row.len()).collect();
+            assert_eq!(row_lengths, vec![2, 2]);
+            assert_eq!(table.rows[0][1], vec![]);
+            assert_eq!(nodes.len(), 3);
+        }
+    }
+
+    mod footnote_tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn test_footnote_reference() {
+            let input = "see[^1]\n[^1]: the note";
             let nodes = build_tree(input);
 
             assert_eq!(
                 nodes,
-                vec![Node::Paragraph(Paragraph {
-                    nodes: vec![Node::Bold(Bold {
+                vec![
+                    Node::Paragraph(Paragraph {
                         nodes: vec![
                             Node::Text(Text {
-                                value: "bold".to_string(),
-                                position: LineSpan { start: 1, end: 1 }
+                                value: "see".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 1,
+                                    end_col: 4,
+                                    bytes: ByteSpan { start: 0, end: 3 }
+                                }
+                            }),
+                            Node::FootnoteRef(FootnoteRef {
+                                id: "1".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 4,
+                                    end_col: 8,
+                                    bytes: ByteSpan { start: 3, end: 7 }
+                                }
+                            })
+                        ],
+                        position: LineSpan {
+                            start: 1,
+                            end: 1,
+                            start_col: 1,
+                            end_col: 8,
+                            bytes: ByteSpan { start: 0, end: 7 }
+                        }
+                    }),
+                    Node::FootnoteDef(FootnoteDef {
+                        id: "1".to_string(),
+                        nodes: vec![
+                            Node::Text(Text {
+                                value: "the".to_string(),
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 7,
+                                    end_col: 10,
+                                    bytes: ByteSpan { start: 14, end: 17 }
+                                }
                             }),
                             Node::Whitespace(Whitespace {
-                                position: LineSpan { start: 1, end: 1 }
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 10,
+                                    end_col: 11,
+                                    bytes: ByteSpan { start: 17, end: 18 }
+                                }
                             }),
                             Node::Text(Text {
-                                value: "text".to_string(),
-                                position: LineSpan { start: 1, end: 1 }
-                            }),
+                                value: "note".to_string(),
+                                position: LineSpan {
+                                    start: 2,
+                                    end: 2,
+                                    start_col: 11,
+                                    end_col: 15,
+                                    bytes: ByteSpan { start: 18, end: 22 }
+                                }
+                            })
                         ],
-                        position: LineSpan { start: 1, end: 1 }
-                    })],
-                    position: LineSpan { start: 1, end: 1 }
-                },)],
+                        position: LineSpan {
+                            start: 2,
+                            end: 2,
+                            start_col: 1,
+                            end_col: 15,
+                            bytes: ByteSpan { start: 8, end: 22 }
+                        }
+                    })
+                ]
             )
         }
 
         #[test]
-        fn test_unclosed_bold_marker() {
-            let input = "**bold text";
+        fn test_footnote_definition() {
+            let input = "[^note]: some *text*";
             let nodes = build_tree(input);
 
             assert_eq!(
                 nodes,
-                vec![Node::Paragraph(Paragraph {
+                vec![Node::FootnoteDef(FootnoteDef {
+                    id: "note".to_string(),
                     nodes: vec![
                         Node::Text(Text {
-                            value: "**".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
-                        }),
-                        Node::Text(Text {
-                            value: "bold".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            value: "some".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 10,
+                                end_col: 14,
+                                bytes: ByteSpan { start: 9, end: 13 }
+                            }
                         }),
                         Node::Whitespace(Whitespace {
-                            position: LineSpan { start: 1, end: 1 }
-                        }),
-                        Node::Text(Text {
-                            value: "text".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 14,
+                                end_col: 15,
+                                bytes: ByteSpan { start: 13, end: 14 }
+                            }
                         }),
+                        Node::Italic(Italic {
+                            nodes: vec![Node::Text(Text {
+                                value: "text".to_string(),
+                                position: LineSpan {
+                                    start: 1,
+                                    end: 1,
+                                    start_col: 16,
+                                    end_col: 20,
+                                    bytes: ByteSpan { start: 15, end: 19 }
+                                }
+                            })],
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 15,
+                                end_col: 21,
+                                bytes: ByteSpan { start: 14, end: 20 }
+                            }
+                        })
                     ],
-                    position: LineSpan { start: 1, end: 1 }
-                },)],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 21,
+                        bytes: ByteSpan { start: 0, end: 20 }
+                    }
+                })]
             )
         }
-    }
-
-    mod paragraph_tests {
-        use super::*;
-        use pretty_assertions::assert_eq;
 
         #[test]
-        fn test_plain_text() {
-            let input = "normal text";
+        fn test_undefined_footnote_and_caret_are_text() {
+            let input = "[^missing] ^";
             let nodes = build_tree(input);
 
             assert_eq!(
@@ -1471,48 +9021,73 @@ mod tests {
                 vec![Node::Paragraph(Paragraph {
                     nodes: vec![
                         Node::Text(Text {
-                            value: "normal".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            value: "[".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 1,
+                                end_col: 2,
+                                bytes: ByteSpan { start: 0, end: 1 }
+                            }
                         }),
-                        Node::Whitespace(Whitespace {
-                            position: LineSpan { start: 1, end: 1 }
+                        Node::Text(Text {
+                            value: "^".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 2,
+                                end_col: 3,
+                                bytes: ByteSpan { start: 1, end: 2 }
+                            }
                         }),
                         Node::Text(Text {
-                            value: "text".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
+                            value: "missing".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 3,
+                                end_col: 10,
+                                bytes: ByteSpan { start: 2, end: 9 }
+                            }
+                        }),
+                        Node::Text(Text {
+                            value: "]".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 10,
+                                end_col: 11,
+                                bytes: ByteSpan { start: 9, end: 10 }
+                            }
+                        }),
+                        Node::Whitespace(Whitespace {
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 11,
+                                end_col: 12,
+                                bytes: ByteSpan { start: 10, end: 11 }
+                            }
                         }),
+                        Node::Text(Text {
+                            value: "^".to_string(),
+                            position: LineSpan {
+                                start: 1,
+                                end: 1,
+                                start_col: 12,
+                                end_col: 13,
+                                bytes: ByteSpan { start: 11, end: 12 }
+                            }
+                        })
                     ],
-                    position: LineSpan { start: 1, end: 1 }
-                },)],
-            )
-        }
-
-        #[test]
-        fn test_break() {
-            let input = "normal\n\ntext";
-            let nodes = build_tree(input);
-
-            assert_eq!(
-                nodes,
-                vec![
-                    Node::Paragraph(Paragraph {
-                        nodes: vec![Node::Text(Text {
-                            value: "normal".to_string(),
-                            position: LineSpan { start: 1, end: 1 }
-                        }),],
-                        position: LineSpan { start: 1, end: 1 }
-                    },),
-                    Node::Eol(Eol {
-                        position: LineSpan { start: 2, end: 2 }
-                    }),
-                    Node::Paragraph(Paragraph {
-                        nodes: vec![Node::Text(Text {
-                            value: "text".to_string(),
-                            position: LineSpan { start: 3, end: 3 }
-                        }),],
-                        position: LineSpan { start: 3, end: 3 }
-                    },),
-                ],
+                    position: LineSpan {
+                        start: 1,
+                        end: 1,
+                        start_col: 1,
+                        end_col: 13,
+                        bytes: ByteSpan { start: 0, end: 12 }
+                    }
+                })]
             )
         }
     }
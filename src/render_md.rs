@@ -0,0 +1,697 @@
+use std::fmt::Write;
+
+use crate::{
+    lexer::is_escapable,
+    tree::{to_plain_text, AlertType, Alignment, List, ListItem, Node, Text},
+};
+
+/// Options that change how the tree is written back to Markdown or to plain text.
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownOptions {
+    /// Wraps header, paragraph and list text at this many columns.
+    pub wrap_width: Option<usize>,
+}
+
+/// Renders the given tree back to Markdown source.
+///
+/// Block nodes are written one per line and a top-level `Eol` becomes a blank line,
+/// so parsing the output yields the same tree for headers, paragraphs, lists and emphasis.
+pub fn to_markdown(nodes: &[Node]) -> String {
+    to_markdown_with_options(nodes, &MarkdownOptions::default())
+}
+
+/// Renders the given tree back to Markdown source with the given options.
+///
+/// With `wrap_width` set, paragraphs and list items are word-wrapped to that many columns.
+/// Wrapped list lines are indented to line up with the item text, and code spans are never split.
+/// A word that would start a list, header, quote or code block at the start of a wrapped line
+/// is escaped there. Words are never split, so a word wider than the width overflows its line.
+pub fn to_markdown_with_options(nodes: &[Node], options: &MarkdownOptions) -> String {
+    let mut markdown = String::new();
+    for (ix, node) in nodes.iter().enumerate() {
+        if ix > 0 {
+            markdown.push('\n');
+        }
+        render_block(node, options, &mut markdown);
+    }
+    markdown
+}
+
+/// Returns the readable text of the document like `to_plain_text`, with the given options.
+///
+/// With `wrap_width` set, headers, paragraphs and list items are word-wrapped to that many
+/// columns, and code spans are never split. A word wider than the width overflows its line.
+pub fn to_plain_text_with_options(nodes: &[Node], options: &MarkdownOptions) -> String {
+    let Some(width) = options.wrap_width else {
+        return to_plain_text(nodes);
+    };
+    let mut text = String::new();
+    wrap_plain_blocks(nodes, width, &mut text);
+    text
+}
+
+/// Writes the plain text of the blocks one per line, wrapped at `width`.
+fn wrap_plain_blocks(nodes: &[Node], width: usize, text: &mut String) {
+    for (ix, node) in nodes.iter().enumerate() {
+        // An `Eol` already ends its own line
+        if ix > 0 && !matches!(nodes[ix - 1], Node::Eol(_)) {
+            text.push('\n');
+        }
+        match node {
+            Node::Header(_)
+            | Node::Paragraph(_)
+            | Node::UnorderedList(_)
+            | Node::OrderedList(_)
+            | Node::ListItem(_) => {
+                let (inner, children) = node.nested();
+                let mut words = vec![String::new()];
+                split_plain_words(inner, &mut words);
+                wrap_plain_words(&words, width, text);
+                for child in children {
+                    text.push('\n');
+                    wrap_plain_blocks(std::slice::from_ref(child), width, text);
+                }
+            }
+            Node::List(list) => wrap_plain_blocks(&list.items, width, text),
+            Node::Alert(alert) => wrap_plain_blocks(&alert.nodes, width, text),
+            _ => {
+                let _ = write!(text, "{}", node);
+            }
+        }
+    }
+}
+
+/// Splits the plain text of the inline nodes into `words` at each whitespace.
+///
+/// A word that ends a line ends with `\n`. Emphasis is split along with its content,
+/// while code spans and links are kept whole.
+fn split_plain_words(nodes: &[Node], words: &mut Vec<String>) {
+    for node in nodes {
+        match node {
+            Node::Whitespace(_) => words.push(String::new()),
+            Node::HardBreak(_) | Node::Eol(_) => {
+                if let Some(word) = words.last_mut() {
+                    word.push('\n');
+                }
+                words.push(String::new());
+            }
+            Node::Italic(_) | Node::Bold(_) | Node::Strikethrough(_) => {
+                split_plain_words(node.nested().0, words)
+            }
+            _ => {
+                if let Some(word) = words.last_mut() {
+                    let _ = write!(word, "{}", node);
+                }
+            }
+        }
+    }
+}
+
+/// Writes the words separated by spaces, starting a new line before a word that would
+/// go past `width`.
+fn wrap_plain_words(words: &[String], width: usize, text: &mut String) {
+    let mut line_len = 0;
+    let mut is_line_start = true;
+    for word in words.iter().filter(|word| !word.is_empty()) {
+        let value = word.trim_end_matches('\n');
+        let word_len = value.chars().count();
+        if !is_line_start && word_len > 0 {
+            if line_len + 1 + word_len > width {
+                text.push('\n');
+                line_len = 0;
+            } else {
+                text.push(' ');
+                line_len += 1;
+            }
+        }
+        text.push_str(value);
+        line_len += word_len;
+        is_line_start = false;
+        if value.len() < word.len() {
+            text.push('\n');
+            line_len = 0;
+            is_line_start = true;
+        }
+    }
+}
+
+fn render_block(node: &Node, options: &MarkdownOptions, markdown: &mut String) {
+    match node {
+        Node::Header(header) => {
+            markdown.push_str(&"#".repeat(header.level));
+            markdown.push(' ');
+            render_inline(&header.nodes, markdown);
+        }
+        Node::Paragraph(paragraph) => render_text(&paragraph.nodes, 0, true, options, markdown),
+        Node::UnorderedList(list) => {
+            let marker = format!("{}- {}", " ".repeat(list.level), checkbox(list.checked));
+            markdown.push_str(&marker);
+            render_item_text(&list.nodes, marker.len(), options, markdown);
+            render_children(&list.children, options, markdown);
+        }
+        Node::OrderedList(list) => {
+            let marker = format!("{}{}. ", " ".repeat(list.level), list.start);
+            markdown.push_str(&marker);
+            render_item_text(&list.nodes, marker.len(), options, markdown);
+            render_children(&list.children, options, markdown);
+        }
+        Node::List(list) => render_list(list, 0, options, markdown),
+        Node::ListItem(item) => render_list_item(item, "- ".to_string(), 0, options, markdown),
+        Node::CodeBlock(code_block) => {
+            markdown.push_str("```");
+            if let Some(language) = &code_block.language {
+                markdown.push_str(language);
+            }
+            markdown.push('\n');
+            if !code_block.value.is_empty() {
+                markdown.push_str(&code_block.value);
+                markdown.push('\n');
+            }
+            markdown.push_str("```");
+        }
+        Node::Alert(alert) => {
+            let alert_type = match alert.alert_type {
+                AlertType::Note => "note",
+                AlertType::Tip => "tip",
+                AlertType::Important => "important",
+                AlertType::Warning => "warning",
+                AlertType::Caution => "caution",
+            };
+            let _ = writeln!(markdown, ":::{}", alert_type);
+            markdown.push_str(&to_markdown_with_options(&alert.nodes, options));
+            markdown.push_str("\n:::");
+        }
+        Node::TableOfContents(_) => markdown.push_str("[TOC]"),
+        Node::FootnoteDef(footnote_def) => {
+            let _ = write!(markdown, "[^{}]:", footnote_def.id);
+            if !footnote_def.nodes.is_empty() {
+                markdown.push(' ');
+                render_inline(&footnote_def.nodes, markdown);
+            }
+        }
+        Node::FrontMatter(front_matter) => {
+            markdown.push_str("---\n");
+            if !front_matter.raw.is_empty() {
+                markdown.push_str(&front_matter.raw);
+                markdown.push('\n');
+            }
+            markdown.push_str("---");
+        }
+        Node::Table(table) => {
+            render_table_row(&table.headers, markdown);
+            markdown.push_str("\n|");
+            for alignment in &table.alignments {
+                markdown.push_str(match alignment {
+                    Alignment::None => " --- |",
+                    Alignment::Left => " :-- |",
+                    Alignment::Center => " :-: |",
+                    Alignment::Right => " --: |",
+                });
+            }
+            for row in &table.rows {
+                markdown.push('\n');
+                render_table_row(row, markdown);
+            }
+        }
+        // A blank line between blocks
+        Node::Eol(_) => {}
+        _ => render_inline(std::slice::from_ref(node), markdown),
+    }
+}
+
+fn render_table_row(cells: &[Vec<Node>], markdown: &mut String) {
+    markdown.push('|');
+    for cell in cells {
+        markdown.push(' ');
+        render_inline(cell, markdown);
+        markdown.push_str(" |");
+    }
+}
+
+fn render_children(children: &[Node], options: &MarkdownOptions, markdown: &mut String) {
+    for child in children {
+        markdown.push('\n');
+        render_block(child, options, markdown);
+    }
+}
+
+/// Returns the task list checkbox written after a list marker.
+fn checkbox(checked: Option<bool>) -> &'static str {
+    match checked {
+        Some(true) => "[x] ",
+        Some(false) => "[ ] ",
+        None => "",
+    }
+}
+
+/// Renders a grouped list at the given nesting level, one item per line.
+///
+/// Ordered items are numbered from the list's `start`.
+fn render_list(list: &List, level: usize, options: &MarkdownOptions, markdown: &mut String) {
+    for (ix, item) in list.items.iter().enumerate() {
+        if ix > 0 {
+            markdown.push('\n');
+        }
+        let Node::ListItem(item) = item else {
+            render_block(item, options, markdown);
+            continue;
+        };
+        let marker = match list.ordered {
+            true => format!("{}. ", list.start + ix),
+            false => "- ".to_string(),
+        };
+        render_list_item(item, marker, level, options, markdown);
+    }
+}
+
+fn render_list_item(
+    item: &ListItem,
+    marker: String,
+    level: usize,
+    options: &MarkdownOptions,
+    markdown: &mut String,
+) {
+    let marker = format!("{}{}{}", " ".repeat(level), marker, checkbox(item.checked));
+    markdown.push_str(&marker);
+    render_item_text(&item.nodes, marker.len(), options, markdown);
+    for child in &item.children {
+        markdown.push('\n');
+        match child {
+            Node::List(list) => render_list(list, level + 1, options, markdown),
+            _ => render_block(child, options, markdown),
+        }
+    }
+}
+
+/// Renders the text of a list item, followed by its continuation paragraphs.
+///
+/// Each continuation paragraph comes after a blank line, with its lines indented to `indent`.
+fn render_item_text(
+    nodes: &[Node],
+    indent: usize,
+    options: &MarkdownOptions,
+    markdown: &mut String,
+) {
+    let text_len = nodes
+        .iter()
+        .position(|node| matches!(node, Node::Paragraph(_)))
+        .unwrap_or(nodes.len());
+    render_text(&nodes[..text_len], indent, false, options, markdown);
+    for node in &nodes[text_len..] {
+        let Node::Paragraph(paragraph) = node else {
+            continue;
+        };
+        markdown.push('\n');
+        for line in paragraph.nodes.split(|node| matches!(node, Node::Eol(_))) {
+            let _ = write!(markdown, "\n{}", " ".repeat(indent));
+            render_text(line, indent, true, options, markdown);
+        }
+    }
+}
+
+/// Renders the text of a paragraph or list item, wrapped if `wrap_width` is set.
+///
+/// `indent` is the column the text starts at, and wrapped lines are indented to it.
+/// `at_line_start` is whether the text starts a line, rather than following a list marker.
+fn render_text(
+    nodes: &[Node],
+    indent: usize,
+    at_line_start: bool,
+    options: &MarkdownOptions,
+    markdown: &mut String,
+) {
+    let Some(width) = options.wrap_width else {
+        let mut at_line_start = at_line_start;
+        for (ix, node) in nodes.iter().enumerate() {
+            match node {
+                Node::Text(text) if at_line_start => {
+                    render_line_start(text, nodes.get(ix + 1), indent, markdown)
+                }
+                _ => render_inline(std::slice::from_ref(node), markdown),
+            }
+            // The line after a hard break starts at the text's indentation too
+            if matches!(node, Node::HardBreak(_)) {
+                markdown.push_str(&" ".repeat(indent));
+            }
+            at_line_start = matches!(node, Node::HardBreak(_) | Node::Eol(_));
+        }
+        return;
+    };
+
+    let mut words = vec![String::new()];
+    split_words(nodes, &mut words);
+
+    let mut line_len = indent;
+    let mut is_line_start = at_line_start;
+    let mut is_first = true;
+    for word in words.iter().filter(|word| !word.is_empty()) {
+        // A word longer than the width overflows its line, since words are never split
+        let word_len = word.chars().count();
+        if !is_first && !is_line_start && line_len + 1 + word_len > width {
+            let _ = write!(markdown, "\n{}", " ".repeat(indent));
+            line_len = indent;
+            is_line_start = true;
+        }
+        if !is_first && !is_line_start {
+            markdown.push(' ');
+            line_len += 1;
+        }
+        if is_line_start {
+            // The word may end the line, where a lone `-` or `#` would start a block as well
+            let escape_at = line_start_escape(word, true, indent);
+            push_escaped(word, escape_at, markdown);
+            line_len += usize::from(escape_at.is_some());
+        } else {
+            markdown.push_str(word);
+        }
+        line_len += word_len;
+        is_line_start = false;
+        is_first = false;
+        if word.ends_with('\n') {
+            markdown.push_str(&" ".repeat(indent));
+            line_len = indent;
+            is_line_start = true;
+        }
+    }
+}
+
+/// Renders the inline nodes into `words`, starting a new word at each whitespace.
+///
+/// Emphasis is split along with its content, while code spans, links and images
+/// are kept whole.
+fn split_words(nodes: &[Node], words: &mut Vec<String>) {
+    for node in nodes {
+        let (marker, inner) = match node {
+            Node::Whitespace(_) => {
+                words.push(String::new());
+                continue;
+            }
+            // A hard break ends its word and the line with it
+            Node::HardBreak(_) => {
+                if let Some(word) = words.last_mut() {
+                    render_inline(std::slice::from_ref(node), word);
+                }
+                words.push(String::new());
+                continue;
+            }
+            Node::Italic(italic) => ("*", &italic.nodes),
+            Node::Bold(bold) => ("**", &bold.nodes),
+            Node::Strikethrough(strikethrough) => ("~~", &strikethrough.nodes),
+            _ => {
+                if let Some(word) = words.last_mut() {
+                    render_inline(std::slice::from_ref(node), word);
+                }
+                continue;
+            }
+        };
+        if let Some(word) = words.last_mut() {
+            word.push_str(marker);
+        }
+        split_words(inner, words);
+        if let Some(word) = words.last_mut() {
+            word.push_str(marker);
+        }
+    }
+}
+
+fn render_inline(nodes: &[Node], markdown: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Paragraph(paragraph) => render_inline(&paragraph.nodes, markdown),
+            Node::Text(text) => {
+                if is_escaped(text) {
+                    markdown.push('\\');
+                }
+                markdown.push_str(&text.value);
+            }
+            Node::Italic(italic) => {
+                markdown.push('*');
+                render_inline(&italic.nodes, markdown);
+                markdown.push('*');
+            }
+            Node::Bold(bold) => {
+                markdown.push_str("**");
+                render_inline(&bold.nodes, markdown);
+                markdown.push_str("**");
+            }
+            Node::Strikethrough(strikethrough) => {
+                markdown.push_str("~~");
+                render_inline(&strikethrough.nodes, markdown);
+                markdown.push_str("~~");
+            }
+            Node::InlineCode(inline_code) => {
+                let value = &inline_code.value;
+                // Pad the content so the parser does not strip its own spaces
+                if value.starts_with(' ') && value.ends_with(' ') && value.trim() != "" {
+                    let _ = write!(markdown, "` {} `", value);
+                } else {
+                    let _ = write!(markdown, "`{}`", value);
+                }
+            }
+            Node::Link(link) => {
+                markdown.push('[');
+                render_inline(&link.text, markdown);
+                let _ = write!(markdown, "]({}", link.url);
+                if let Some(title) = &link.title {
+                    let _ = write!(markdown, " \"{}\"", title);
+                }
+                markdown.push(')');
+            }
+            Node::Image(image) => {
+                let _ = write!(markdown, "![{}]({}", image.alt, image.src);
+                if let Some(title) = &image.title {
+                    let _ = write!(markdown, " \"{}\"", title);
+                }
+                markdown.push(')');
+            }
+            Node::FootnoteRef(footnote_ref) => {
+                let _ = write!(markdown, "[^{}]", footnote_ref.id);
+            }
+            Node::Whitespace(whitespace) => {
+                // A run of spaces is one node, as wide as the run
+                let position = &whitespace.position;
+                let width = position.end_col.saturating_sub(position.start_col) as usize;
+                markdown.push_str(&" ".repeat(width.max(1)));
+            }
+            Node::HardBreak(hard_break) => {
+                // Keep trailing spaces as wide as they were, and a backslash otherwise
+                let position = &hard_break.position;
+                match position.end_col.saturating_sub(position.start_col) as usize {
+                    width if width >= 2 => markdown.push_str(&" ".repeat(width)),
+                    _ => markdown.push('\\'),
+                }
+                markdown.push('\n');
+            }
+            Node::Eol(_) => markdown.push('\n'),
+            _ => render_block(node, &MarkdownOptions::default(), markdown),
+        }
+    }
+}
+
+/// Renders text that starts a line, escaping it if it would start a block there.
+///
+/// A list marker is escaped at any indentation, while a header, quote or code fence
+/// marker only starts a block at the start of a top-level line, where `indent` is 0.
+fn render_line_start(text: &Text, next: Option<&Node>, indent: usize, markdown: &mut String) {
+    let value = text.value.as_str();
+    if is_escaped(text) {
+        markdown.push('\\');
+        markdown.push_str(value);
+        return;
+    }
+    let before_space = matches!(next, Some(Node::Whitespace(_)));
+    push_escaped(
+        value,
+        line_start_escape(value, before_space, indent),
+        markdown,
+    );
+}
+
+/// Returns where text that starts a line needs a backslash, if it would start a block there.
+///
+/// `before_space` is whether whitespace follows the text, which a list or header marker needs.
+fn line_start_escape(value: &str, before_space: bool, indent: usize) -> Option<usize> {
+    let digits = value.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 && &value[digits..] == "." && before_space {
+        // `1\.` keeps the number and escapes the dot
+        Some(digits)
+    } else if (value == "-" && before_space)
+        || value.starts_with("- ")
+        || (indent == 0
+            && (value.starts_with("```")
+                || value.starts_with('>')
+                || (value.len() <= 6 && value.chars().all(|c| c == '#') && before_space)))
+    {
+        Some(0)
+    } else {
+        None
+    }
+}
+
+/// Writes the text with a backslash inserted at `escape_at`, if it is set.
+fn push_escaped(value: &str, escape_at: Option<usize>, markdown: &mut String) {
+    match escape_at {
+        Some(ix) => {
+            markdown.push_str(&value[..ix]);
+            markdown.push('\\');
+            markdown.push_str(&value[ix..]);
+        }
+        None => markdown.push_str(value),
+    }
+}
+
+/// Checks whether the text needs a backslash so that it is not read as markup.
+///
+/// Text that was escaped in the source is one byte longer there than its value, for
+/// the backslash. Without a source span, a run of a marker character is escaped instead.
+fn is_escaped(text: &Text) -> bool {
+    let Some(first) = text.value.chars().next() else {
+        return false;
+    };
+    let bytes = &text.position.bytes;
+    if bytes.start < bytes.end {
+        is_escapable(first) && bytes.range().len() == text.value.len() + 1
+    } else {
+        matches!(first, '*' | '_' | '`' | '[' | ']' | '~' | '|')
+            && text.value.chars().all(|c| c == first)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{build_tree, Parser};
+    use crate::tree::clear_positions;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_round_trip() {
+        let fixtures = [
+            "# Title\ntext\n\nnext",
+            "## Sub  title with *italic*\n\n\n### **bold** header",
+            "**bold** and *italic* text\n\n- item 1\n - item 1.1\n  - item 1.1.1\n- item 2",
+            "1. one\n2. two\n - nested",
+            "- item 1\n - item 1.1\n - item 1.2\n  - item 1.2.1\n   - item 1.2.1.1\n - item 1.3",
+            "- [ ] todo\n- [x] done\n - [ ] nested",
+            "`  padded  ` code",
+            "- a\n\n  more text\n  and more\n- b",
+            "---\ntitle: Hello\n---\n# Title",
+            "hard  \nbreaks\\\nhere",
+            "a note[^1] ^\n\n[^1]: the *note*",
+            "| a | *b* |\n| :-- | --: |\n| 1 | 2 |\n| 3 |  |\n\nafter",
+            "\\*not italic\\* and \\~~not struck\\~~\n\\#notheader \\[not a link\\]",
+            "| a \\| b | c |\n| --- | --- |\n| 1 | 2 |",
+            "\\```not a fence\nstill a paragraph\n\n- item",
+        ];
+
+        for input in fixtures {
+            let nodes = build_tree(input);
+            let markdown = to_markdown(&nodes);
+
+            assert_eq!(build_tree(&markdown), nodes, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_escape_without_positions() {
+        let nodes = clear_positions(Parser::new().parse("\\*a\\* b"));
+
+        assert_eq!(to_markdown(&nodes), "\\*a\\* b");
+    }
+
+    #[test]
+    fn test_escape_line_start() {
+        let parser = Parser::new();
+        let nodes = clear_positions(
+            parser.parse("\\```\ncode\n\n\\# not a header\n\n\\> not a quote\n\n\\- not a list"),
+        );
+        let markdown = to_markdown(&nodes);
+
+        assert_eq!(clear_positions(parser.parse(&markdown)), nodes);
+
+        let nodes = Parser::new().ordered_lists(false).parse("1. not a list");
+        assert_eq!(to_markdown(&nodes), "1\\. not a list");
+    }
+
+    #[test]
+    fn test_code_block_tabs_round_trip() {
+        let input = "```go\nfunc main() {\n\tif x {\n\t\treturn\n\t}\n}\n```";
+        let markdown = to_markdown(&build_tree(input));
+
+        assert_eq!(markdown, input);
+    }
+
+    #[test]
+    fn test_wrap_width() {
+        let input = "A long paragraph that keeps going with *some emphasis* and `a code span` until the end.\n- a list item that is also long enough to wrap";
+        let options = MarkdownOptions {
+            wrap_width: Some(40),
+        };
+        let markdown = to_markdown_with_options(&build_tree(input), &options);
+
+        assert_eq!(
+            markdown,
+            "A long paragraph that keeps going with\n\
+             *some emphasis* and `a code span` until\n\
+             the end.\n\
+             - a list item that is also long enough\n  \
+             to wrap"
+        );
+    }
+
+    #[test]
+    fn test_plain_text_wrap_width() {
+        let input = "A long paragraph that keeps going with *some emphasis* and `a code span` until the end.\n- a list item that is also long enough to wrap\n - nested";
+        let options = MarkdownOptions {
+            wrap_width: Some(40),
+        };
+        let text = to_plain_text_with_options(&build_tree(input), &options);
+
+        assert_eq!(
+            text,
+            "A long paragraph that keeps going with\n\
+             some emphasis and a code span until the\n\
+             end.\n\
+             a list item that is also long enough to\n\
+             wrap\n\
+             nested"
+        );
+        assert_eq!(
+            to_plain_text_with_options(&build_tree(input), &MarkdownOptions::default()),
+            to_plain_text(&build_tree(input))
+        );
+    }
+
+    #[test]
+    fn test_wrap_width_keeps_block_markers_inside_lines() {
+        let input = "one two - three 1. four # five > six";
+        let options = MarkdownOptions {
+            wrap_width: Some(8),
+        };
+        let nodes = clear_positions(Parser::new().parse(input));
+        let markdown = to_markdown_with_options(&nodes, &options);
+
+        assert_eq!(markdown, "one two\n\\- three\n1\\. four\n\\# five\n\\> six");
+        assert!(markdown.lines().all(|line| line.chars().count() <= 8));
+        // Every wrapped line reads back as a paragraph, not as a list, header or quote
+        let reparsed = Parser::new().parse(&markdown);
+        assert!(reparsed
+            .iter()
+            .all(|node| matches!(node, Node::Paragraph(_))));
+        let words = |nodes: &[Node]| {
+            to_plain_text(nodes)
+                .split_whitespace()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(words(&reparsed), words(&nodes));
+    }
+
+    #[test]
+    fn test_list_indentation() {
+        let input = "- item 1\n - item 1.1\n  - item 1.1.1";
+        let markdown = to_markdown(&build_tree(input));
+
+        assert_eq!(markdown, input);
+    }
+}
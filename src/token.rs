@@ -1,3 +1,4 @@
+/// The kind of a token produced by `lex`.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum TokenType {
     Header,             // #
@@ -5,12 +6,14 @@ pub enum TokenType {
     Whitespace,         // ' '
     Eol,                // \n (End of line)
     UnorderedList,      // -
+    OrderedList,        // 1.
     BlockQuote,         // >
     CodeBlock,          // ```
     InlineCode,         // `
     Annotation,         // ^
     Bold,               // **
     Italic,             // *
+    Strikethrough,      // ~~
     CarlyBracketOpen,   // {
     CarlyBracketClose,  // }
     Colon,              // :
@@ -23,12 +26,41 @@ pub enum TokenType {
     AlertStart,         // :::<type>
     AlertEnd,           // :::
     Exclamation,        // !
+    Pipe,               // |
     Unknown,
 }
 
+/// A piece of the document as produced by `lex`, with its position in the input.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Token {
     pub token_type: TokenType,
-    pub value: String, // actutual value in the file
-    pub line: usize,   // line number in the file
+    pub value: String,     // actutual value in the file
+    pub line: usize,       // line number in the file
+    pub column: usize,     // 1-based column of the first character in the line
+    pub start_byte: usize, // byte offset in the input where the token's source text starts
+    pub end_byte: usize,   // byte offset just after the token's source text
+}
+
+/// A token as produced by `lex_borrowed`, whose value borrows from the input.
+#[derive(Debug, PartialEq, Clone)]
+pub struct BorrowedToken<'a> {
+    pub token_type: TokenType,
+    pub value: &'a str,    // actual value in the file
+    pub line: usize,       // line number in the file
+    pub column: usize,     // 1-based column of the first character in the line
+    pub start_byte: usize, // byte offset in the input where the token's source text starts
+    pub end_byte: usize,   // byte offset just after the token's source text
+}
+
+impl From<BorrowedToken<'_>> for Token {
+    fn from(token: BorrowedToken<'_>) -> Self {
+        Self {
+            token_type: token.token_type,
+            value: token.value.to_string(),
+            line: token.line,
+            column: token.column,
+            start_byte: token.start_byte,
+            end_byte: token.end_byte,
+        }
+    }
 }
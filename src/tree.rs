@@ -1,16 +1,37 @@
-use serde::Serialize;
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Range,
+};
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub enum Node {
     // Block contents
     Header(Header),
     Paragraph(Paragraph),
     UnorderedList(UnorderedList),
+    OrderedList(OrderedList),
+    List(List),
+    ListItem(ListItem),
+    CodeBlock(CodeBlock),
+    TableOfContents(TableOfContents),
+    FrontMatter(FrontMatter),
+    Table(Table),
+    FootnoteDef(FootnoteDef),
     // Inline contents
     Text(Text),
     Italic(Italic),
     Bold(Bold),
+    Strikethrough(Strikethrough),
+    InlineCode(InlineCode),
+    Link(Link),
+    Image(Image),
+    FootnoteRef(FootnoteRef),
     Whitespace(Whitespace),
+    HardBreak(HardBreak),
     Alert(Alert),
     Eol(Eol),
 }
@@ -21,24 +42,904 @@ impl Node {
             Node::Header(header) => header.position(),
             Node::Paragraph(paragraph) => paragraph.position(),
             Node::UnorderedList(unordered_list) => unordered_list.position(),
+            Node::OrderedList(ordered_list) => ordered_list.position(),
+            Node::List(list) => list.position(),
+            Node::ListItem(list_item) => list_item.position(),
+            Node::CodeBlock(code_block) => code_block.position(),
+            Node::TableOfContents(table_of_contents) => table_of_contents.position(),
+            Node::FrontMatter(front_matter) => front_matter.position(),
+            Node::Table(table) => table.position(),
+            Node::FootnoteDef(footnote_def) => footnote_def.position(),
             Node::Text(text) => text.position(),
             Node::Italic(italic) => italic.position(),
             Node::Bold(bold) => bold.position(),
+            Node::Strikethrough(strikethrough) => strikethrough.position(),
+            Node::InlineCode(inline_code) => inline_code.position(),
+            Node::Link(link) => link.position(),
+            Node::Image(image) => image.position(),
+            Node::FootnoteRef(footnote_ref) => footnote_ref.position(),
             Node::Whitespace(whitespace) => whitespace.position(),
+            Node::HardBreak(hard_break) => hard_break.position(),
             Node::Alert(alert) => alert.position(),
             Node::Eol(eol) => eol.position(),
         }
     }
+
+    /// Returns the slice of `input` that this node was parsed from.
+    ///
+    /// `input` must be the string the tree was built from. Returns `None` when
+    /// the node has no source span, e.g. when it was built by hand.
+    pub fn raw<'a>(&self, input: &'a str) -> Option<&'a str> {
+        let bytes = &self.position().bytes;
+        if bytes.start >= bytes.end {
+            return None;
+        }
+        input.get(bytes.range())
+    }
+
+    /// Returns every node nested in this one, depth-first in document order.
+    ///
+    /// The node itself is not included. See `flatten` for the order.
+    pub fn descendants(&self) -> impl Iterator<Item = &Node> {
+        let mut descendants = Descendants { stack: vec![] };
+        descendants.push_nested(self);
+        descendants
+    }
+
+    /// Returns the text of the node and all of its descendants, without any markup.
+    ///
+    /// Unlike `to_string`, the text stays on one line: line breaks become spaces,
+    /// and list children are joined with a space as well.
+    pub fn text_content(&self) -> String {
+        let mut text = plain_text(std::slice::from_ref(self));
+        for child in self.nested().1 {
+            text.push(' ');
+            text.push_str(&child.text_content());
+        }
+        text
+    }
+}
+
+impl Node {
+    /// Returns the nested `nodes` and the list `children` of this node.
+    ///
+    /// Either slice is empty when the node has no such content.
+    pub(crate) fn nested(&self) -> (&[Node], &[Node]) {
+        match self {
+            Node::Header(header) => (&header.nodes, &[]),
+            Node::Paragraph(paragraph) => (&paragraph.nodes, &[]),
+            Node::UnorderedList(unordered_list) => {
+                (&unordered_list.nodes, &unordered_list.children)
+            }
+            Node::OrderedList(ordered_list) => (&ordered_list.nodes, &ordered_list.children),
+            Node::List(list) => (&list.items, &[]),
+            Node::ListItem(list_item) => (&list_item.nodes, &list_item.children),
+            Node::Italic(italic) => (&italic.nodes, &[]),
+            Node::Bold(bold) => (&bold.nodes, &[]),
+            Node::Strikethrough(strikethrough) => (&strikethrough.nodes, &[]),
+            Node::Alert(alert) => (&alert.nodes, &[]),
+            Node::FootnoteDef(footnote_def) => (&footnote_def.nodes, &[]),
+            Node::Link(link) => (&link.text, &[]),
+            Node::CodeBlock(_)
+            | Node::TableOfContents(_)
+            | Node::FrontMatter(_)
+            | Node::Table(_)
+            | Node::Text(_)
+            | Node::InlineCode(_)
+            | Node::Image(_)
+            | Node::FootnoteRef(_)
+            | Node::Whitespace(_)
+            | Node::HardBreak(_)
+            | Node::Eol(_) => (&[], &[]),
+        }
+    }
+
+    /// Returns the nested `nodes`, the list `children` and the nodes of each table cell,
+    /// in document order.
+    ///
+    /// Traversals use this rather than `nested`, so they reach the cells of a table.
+    pub(crate) fn nested_slices(&self) -> impl Iterator<Item = &[Node]> {
+        let (inner, children) = self.nested();
+        let cells = match self {
+            Node::Table(table) => Some(table.headers.iter().chain(table.rows.iter().flatten())),
+            _ => None,
+        };
+        [inner, children]
+            .into_iter()
+            .chain(cells.into_iter().flatten().map(Vec::as_slice))
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        match self {
+            Node::Header(_) => NodeKind::Header,
+            Node::Paragraph(_) => NodeKind::Paragraph,
+            Node::UnorderedList(_) => NodeKind::UnorderedList,
+            Node::OrderedList(_) => NodeKind::OrderedList,
+            Node::List(_) => NodeKind::List,
+            Node::ListItem(_) => NodeKind::ListItem,
+            Node::CodeBlock(_) => NodeKind::CodeBlock,
+            Node::TableOfContents(_) => NodeKind::TableOfContents,
+            Node::FrontMatter(_) => NodeKind::FrontMatter,
+            Node::Table(_) => NodeKind::Table,
+            Node::FootnoteDef(_) => NodeKind::FootnoteDef,
+            Node::Text(_) => NodeKind::Text,
+            Node::Italic(_) => NodeKind::Italic,
+            Node::Bold(_) => NodeKind::Bold,
+            Node::Strikethrough(_) => NodeKind::Strikethrough,
+            Node::InlineCode(_) => NodeKind::InlineCode,
+            Node::Link(_) => NodeKind::Link,
+            Node::Image(_) => NodeKind::Image,
+            Node::FootnoteRef(_) => NodeKind::FootnoteRef,
+            Node::Whitespace(_) => NodeKind::Whitespace,
+            Node::HardBreak(_) => NodeKind::HardBreak,
+            Node::Alert(_) => NodeKind::Alert,
+            Node::Eol(_) => NodeKind::Eol,
+        }
+    }
+}
+
+/// The kind of a `Node`, without its contents.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum NodeKind {
+    Header,
+    Paragraph,
+    UnorderedList,
+    OrderedList,
+    List,
+    ListItem,
+    CodeBlock,
+    TableOfContents,
+    FrontMatter,
+    Table,
+    FootnoteDef,
+    Text,
+    Italic,
+    Bold,
+    Strikethrough,
+    InlineCode,
+    Link,
+    Image,
+    FootnoteRef,
+    Whitespace,
+    HardBreak,
+    Alert,
+    Eol,
+}
+
+/// Returns the text of the nodes without any formatting.
+pub(crate) fn plain_text(nodes: &[Node]) -> String {
+    let mut text = String::new();
+    for node in nodes {
+        match node {
+            Node::Text(t) => text.push_str(&t.value),
+            Node::InlineCode(inline_code) => text.push_str(&inline_code.value),
+            Node::Image(image) => text.push_str(&image.alt),
+            Node::Whitespace(_) | Node::HardBreak(_) | Node::Eol(_) => text.push(' '),
+            // The cells of a table are separated by a space
+            Node::Table(table) => {
+                let cells: Vec<String> = table
+                    .headers
+                    .iter()
+                    .chain(table.rows.iter().flatten())
+                    .map(|cell| plain_text(cell))
+                    .collect();
+                text.push_str(&cells.join(" "));
+            }
+            _ => text.push_str(&plain_text(node.nested().0)),
+        }
+    }
+    text
+}
+
+/// Returns the positions of every node of the given kind, in document order.
+///
+/// Nested `nodes`, list `children` and table cells are searched as well.
+pub fn positions_of(nodes: &[Node], kind: NodeKind) -> Vec<LineSpan> {
+    let mut positions: Vec<LineSpan> = vec![];
+
+    for node in nodes {
+        if node.kind() == kind {
+            positions.push(node.position().clone());
+        }
+        for nested in node.nested_slices() {
+            positions.extend(positions_of(nested, kind));
+        }
+    }
+
+    positions
+}
+
+/// Calls `f` with every node and its nesting depth, in document order.
+///
+/// Top-level nodes are at depth 0, and nested `nodes`, list `children` and
+/// the nodes of table cells are one level deeper than their parent.
+pub fn walk_with_depth(nodes: &[Node], mut f: impl FnMut(usize, &Node)) {
+    walk_nodes_with_depth(nodes, 0, &mut f);
+}
+
+fn walk_nodes_with_depth(nodes: &[Node], depth: usize, f: &mut impl FnMut(usize, &Node)) {
+    for node in nodes {
+        f(depth, node);
+        for nested in node.nested_slices() {
+            walk_nodes_with_depth(nested, depth + 1, f);
+        }
+    }
+}
+
+/// Returns every node of the tree, depth-first in document order.
+///
+/// Each node comes before its nested `nodes`, which come before its list `children`.
+/// The nodes of table cells follow their table, row by row.
+pub fn flatten(nodes: &[Node]) -> impl Iterator<Item = &Node> {
+    Descendants {
+        stack: nodes.iter().rev().collect(),
+    }
+}
+
+/// The iterator behind `flatten` and `Node::descendants`.
+struct Descendants<'a> {
+    stack: Vec<&'a Node>, // the nodes still to visit, the next one last
+}
+
+impl<'a> Descendants<'a> {
+    /// Queues the nodes nested in `node` so that they are visited next, in order.
+    fn push_nested(&mut self, node: &'a Node) {
+        let nested: Vec<&Node> = node.nested_slices().flatten().collect();
+        self.stack.extend(nested.into_iter().rev());
+    }
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = &'a Node;
+
+    fn next(&mut self) -> Option<&'a Node> {
+        let node = self.stack.pop()?;
+        self.push_nested(node);
+        Some(node)
+    }
+}
+
+/// Counts of the blocks and words of a document, as returned by `summary`.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct DocumentSummary {
+    pub headers: [usize; 6], // the number of headers of each level, from 1 to 6
+    pub paragraphs: usize,
+    pub list_items: usize,
+    pub words: usize,     // whitespace-separated words in the text
+    pub last_line: usize, // the last line with content, or 0 for an empty tree
+}
+
+/// Counts the headers, paragraphs, list items and words of the tree, and finds its last line.
+///
+/// Nested `nodes` and list `children` are counted as well. Headers deeper than
+/// level 6, which `Parser::max_header_level` can allow, are not counted.
+/// The tree does not keep the input, so `last_line` is not the number of lines in it:
+/// blank lines after the last block and a trailing newline are not counted.
+pub fn summary(nodes: &[Node]) -> DocumentSummary {
+    fn count(nodes: &[Node], summary: &mut DocumentSummary) {
+        for node in nodes {
+            match node {
+                Node::Header(header) => {
+                    if let Some(headers) = summary.headers.get_mut(header.level.wrapping_sub(1)) {
+                        *headers += 1;
+                    }
+                }
+                Node::Paragraph(_) => summary.paragraphs += 1,
+                Node::UnorderedList(_) | Node::OrderedList(_) | Node::ListItem(_) => {
+                    summary.list_items += 1
+                }
+                Node::Text(text) => summary.words += text.value.split_whitespace().count(),
+                _ => {}
+            }
+            // A blank line is an `Eol` of its own, which is not content
+            if !matches!(node, Node::Eol(_)) {
+                summary.last_line = summary.last_line.max(node.position().end as usize);
+            }
+
+            for nested in node.nested_slices() {
+                count(nested, summary);
+            }
+        }
+    }
+
+    let mut document_summary = DocumentSummary::default();
+    count(nodes, &mut document_summary);
+    document_summary
+}
+
+/// Identifies a node of a tree by its place in document order.
+///
+/// Ids only depend on the shape of the tree, so parsing the same input always gives
+/// the same ids. Data about nodes can be kept in a `HashMap<NodeId, T>` next to the tree.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+pub struct NodeId(pub u64);
+
+/// Returns every node of the tree with its id, in document order.
+///
+/// Nodes are numbered from 0 in the order `walk_with_depth` visits them.
+pub fn node_ids(nodes: &[Node]) -> Vec<(NodeId, &Node)> {
+    fn collect<'a>(nodes: &'a [Node], ids: &mut Vec<(NodeId, &'a Node)>) {
+        for node in nodes {
+            ids.push((NodeId(ids.len() as u64), node));
+            for nested in node.nested_slices() {
+                collect(nested, ids);
+            }
+        }
+    }
+
+    let mut ids = vec![];
+    collect(nodes, &mut ids);
+    ids
+}
+
+/// Returns the innermost node whose span contains the given line.
+///
+/// Nested `nodes`, list `children` and table cells are searched before their parent, so a line of
+/// a header gives its `Text` rather than the `Header`. When several siblings share the line,
+/// the first one in document order is returned.
+pub fn node_at_line(nodes: &[Node], line: usize) -> Option<&Node> {
+    for node in nodes {
+        let innermost = node
+            .nested_slices()
+            .find_map(|nested| node_at_line(nested, line));
+        if innermost.is_some() {
+            return innermost;
+        }
+        let position = node.position();
+        if (position.start as usize..=position.end as usize).contains(&line) {
+            return Some(node);
+        }
+    }
+    None
+}
+
+/// Returns the span from the first to the last position covered by the document.
+///
+/// Nested `nodes` and list `children` are included, so the span holds even if a block's
+/// own position does not cover all of its children. An empty document gives `LineSpan::default()`.
+pub fn document_span(nodes: &[Node]) -> LineSpan {
+    let mut span: Option<LineSpan> = None;
+    walk_with_depth(nodes, |_, node| {
+        let position = node.position();
+        let Some(span) = span.as_mut() else {
+            span = Some(position.clone());
+            return;
+        };
+        if (position.start, position.start_col) < (span.start, span.start_col) {
+            span.start = position.start;
+            span.start_col = position.start_col;
+        }
+        if (position.end, position.end_col) > (span.end, span.end_col) {
+            span.end = position.end;
+            span.end_col = position.end_col;
+        }
+        span.bytes.start = span.bytes.start.min(position.bytes.start);
+        span.bytes.end = span.bytes.end.max(position.bytes.end);
+    });
+    span.unwrap_or_default()
+}
+
+/// Converts the tree to a JSON value, for embedding it in a larger JSON document.
+///
+/// Each node becomes an object keyed by its kind, as with `serde_json::to_string`.
+pub fn to_value(nodes: &[Node]) -> Result<serde_json::Value, serde_json::Error> {
+    serde_json::to_value(nodes)
+}
+
+/// Converts the headers of the tree to a JSON outline of `{ level, text, children }` objects.
+///
+/// Each header holds the deeper headers that follow it up to the next header of its level
+/// or above, so an `H2` is a child of the `H1` before it. Headers before any shallower
+/// header are at the top level.
+pub fn to_outline_json(nodes: &[Node]) -> serde_json::Value {
+    fn close(stack: &mut Vec<(usize, serde_json::Value)>, roots: &mut Vec<serde_json::Value>) {
+        if let Some((_, entry)) = stack.pop() {
+            match stack.last_mut() {
+                Some((_, parent)) => parent["children"].as_array_mut().unwrap().push(entry),
+                None => roots.push(entry),
+            }
+        }
+    }
+
+    let mut roots = vec![];
+    let mut stack: Vec<(usize, serde_json::Value)> = vec![];
+    walk_with_depth(nodes, |_, node| {
+        if let Node::Header(header) = node {
+            while stack
+                .last()
+                .is_some_and(|(level, _)| *level >= header.level)
+            {
+                close(&mut stack, &mut roots);
+            }
+            let entry = serde_json::json!({
+                "level": header.level,
+                "text": plain_text(&header.nodes),
+                "children": [],
+            });
+            stack.push((header.level, entry));
+        }
+    });
+    while !stack.is_empty() {
+        close(&mut stack, &mut roots);
+    }
+    serde_json::Value::Array(roots)
+}
+
+/// Formats the tree as a compact indented outline, one line per node.
+///
+/// Each line shows the node kind, its span as `line:col-line:col` and,
+/// for nodes holding text, a short snippet of it.
+/// Nested nodes are indented by two spaces per level.
+pub fn debug_tree(nodes: &[Node]) -> String {
+    let mut output = String::new();
+    walk_with_depth(nodes, |depth, node| {
+        let position = node.position();
+        output.push_str(&format!(
+            "{}{:?} {}:{}-{}:{}",
+            "  ".repeat(depth),
+            node.kind(),
+            position.start,
+            position.start_col,
+            position.end,
+            position.end_col
+        ));
+        let snippet = match node {
+            Node::Text(text) => Some(text.value.as_str()),
+            Node::InlineCode(inline_code) => Some(inline_code.value.as_str()),
+            Node::CodeBlock(code_block) => Some(code_block.value.as_str()),
+            Node::FrontMatter(front_matter) => Some(front_matter.raw.as_str()),
+            Node::Link(link) => Some(link.url.as_str()),
+            Node::Image(image) => Some(image.src.as_str()),
+            _ => None,
+        };
+        if let Some(snippet) = snippet {
+            let short: String = snippet.chars().take(DEBUG_SNIPPET_LEN).collect();
+            let ellipsis = if short.len() < snippet.len() {
+                "..."
+            } else {
+                ""
+            };
+            output.push_str(&format!(" {:?}{}", short, ellipsis));
+        }
+        output.push('\n');
+    });
+    output
+}
+
+/// The number of characters of text shown per node by `debug_tree`.
+const DEBUG_SNIPPET_LEN: usize = 20;
+
+/// Writes the readable text of the node, without any markup characters.
+///
+/// List items write their children on the following lines.
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Text(text) => f.write_str(&text.value),
+            Node::InlineCode(inline_code) => f.write_str(&inline_code.value),
+            Node::CodeBlock(code_block) => f.write_str(&code_block.value),
+            Node::Image(image) => f.write_str(&image.alt),
+            Node::Whitespace(_) => f.write_str(" "),
+            Node::HardBreak(_) | Node::Eol(_) => f.write_str("\n"),
+            Node::Alert(alert) => write_blocks(&alert.nodes, f),
+            Node::List(list) => write_blocks(&list.items, f),
+            Node::Table(table) => {
+                for (ix, row) in std::iter::once(&table.headers)
+                    .chain(&table.rows)
+                    .enumerate()
+                {
+                    if ix > 0 {
+                        f.write_str("\n")?;
+                    }
+                    for (ix, cell) in row.iter().enumerate() {
+                        if ix > 0 {
+                            f.write_str(" ")?;
+                        }
+                        for node in cell {
+                            write!(f, "{}", node)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            _ => {
+                let (nodes, children) = self.nested();
+                for node in nodes {
+                    write!(f, "{}", node)?;
+                }
+                for child in children {
+                    write!(f, "\n{}", child)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Returns the readable text of the document, one block per line.
+///
+/// Blank lines between blocks are kept, so paragraphs stay apart.
+pub fn to_plain_text(nodes: &[Node]) -> String {
+    struct Blocks<'a>(&'a [Node]);
+
+    impl fmt::Display for Blocks<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write_blocks(self.0, f)
+        }
+    }
+
+    Blocks(nodes).to_string()
+}
+
+fn write_blocks(nodes: &[Node], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (ix, node) in nodes.iter().enumerate() {
+        write!(f, "{}", node)?;
+        // An `Eol` already ends its own line
+        if ix + 1 < nodes.len() && !matches!(node, Node::Eol(_)) {
+            f.write_str("\n")?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns a deep copy of the node with every line number shifted by `line_delta`.
+///
+/// Line numbers that would become negative are clamped to 0.
+/// Byte offsets are left unchanged, since the length of the shifted lines is unknown.
+pub fn clone_offset(node: &Node, line_delta: isize) -> Node {
+    let mut node = node.clone();
+    for_each_position_mut(&mut node, &mut |position| {
+        position.start = shift(position.start, line_delta);
+        position.end = shift(position.end, line_delta);
+    });
+    node
+}
+
+/// Inserts a parsed fragment into the tree so that it starts at the given line.
+///
+/// The fragment is expected to be parsed on its own, starting at line 1.
+/// It is placed before the first node starting at or after `line`, its positions
+/// are moved to start at `line`, and the nodes after it are shifted down by the
+/// number of lines the fragment covers.
+/// Byte offsets are shifted the same way, as if the fragment's lines were inserted
+/// into the document text.
+pub fn insert_at(nodes: &mut Vec<Node>, line: usize, mut fragment: Vec<Node>) {
+    let (Some(first), Some(last)) = (fragment.first(), fragment.last()) else {
+        return;
+    };
+    let fragment_start = first.position().start;
+    let line_count = last.position().end + 1 - fragment_start;
+    let fragment_byte = first.position().bytes.start;
+    // The fragment is followed by a line break, unless it already ends with one
+    let byte_count =
+        last.position().bytes.end - fragment_byte + u32::from(!matches!(last, Node::Eol(_)));
+
+    let index = nodes
+        .iter()
+        .position(|node| node.position().start as usize >= line)
+        .unwrap_or(nodes.len());
+    let insert_byte = match (nodes.get(index), index.checked_sub(1)) {
+        (Some(node), _) => node.position().bytes.start as usize,
+        (None, Some(prev)) => nodes[prev].position().bytes.end as usize + 1,
+        (None, None) => 0,
+    };
+
+    let fragment_delta = line as isize - fragment_start as isize;
+    let fragment_byte_delta = insert_byte as isize - fragment_byte as isize;
+    for node in &mut fragment {
+        for_each_position_mut(node, &mut |position| {
+            position.start = shift(position.start, fragment_delta);
+            position.end = shift(position.end, fragment_delta);
+            position.bytes.start = shift(position.bytes.start, fragment_byte_delta);
+            position.bytes.end = shift(position.bytes.end, fragment_byte_delta);
+        });
+    }
+
+    for node in &mut nodes[index..] {
+        for_each_position_mut(node, &mut |position| {
+            position.start += line_count;
+            position.end += line_count;
+            position.bytes.start += byte_count;
+            position.bytes.end += byte_count;
+        });
+    }
+    nodes.splice(index..index, fragment);
+}
+
+/// Moves a position by `delta`, clamping at 0 and `u32::MAX`.
+fn shift(position: u32, delta: isize) -> u32 {
+    let shifted = (position as isize).saturating_add(delta).max(0);
+    u32::try_from(shifted).unwrap_or(u32::MAX)
+}
+
+/// Converts a line, column or byte offset into a span field, saturating at `u32::MAX`.
+pub(crate) fn span_offset(offset: usize) -> u32 {
+    u32::try_from(offset).unwrap_or(u32::MAX)
+}
+
+/// Compares two nodes by their starting line, then by their ending line.
+///
+/// Nodes on the same lines compare equal, so a stable sort keeps their order.
+pub fn by_position(a: &Node, b: &Node) -> Ordering {
+    let (a, b) = (a.position(), b.position());
+    a.start.cmp(&b.start).then(a.end.cmp(&b.end))
+}
+
+/// Sorts the nodes into line order, e.g. after merging the results of several parses.
+///
+/// Only the top level is sorted, and the positions themselves are left unchanged.
+pub fn sort_nodes(nodes: &mut [Node]) {
+    nodes.sort_by(by_position);
+}
+
+/// Returns a hash of each line of the input, in order.
+///
+/// A line's hash only depends on its own content, so comparing the hashes of two
+/// versions of a document shows which lines changed.
+pub fn line_hashes(input: &str) -> Vec<u64> {
+    input
+        .split('\n')
+        .map(|line| {
+            let mut hasher = DefaultHasher::new();
+            line.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// Returns the indices of the top-level nodes that cover a changed line.
+///
+/// Lines are compared by their hashes from `line_hashes`. Lines that exist in only
+/// one of the versions count as changed.
+pub fn affected_blocks(nodes: &[Node], old_hashes: &[u64], new_hashes: &[u64]) -> Vec<usize> {
+    let line_count = old_hashes.len().max(new_hashes.len());
+    let changed_lines: Vec<usize> = (0..line_count)
+        .filter(|&ix| old_hashes.get(ix) != new_hashes.get(ix))
+        .map(|ix| ix + 1)
+        .collect();
+
+    nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| {
+            let position = node.position();
+            changed_lines
+                .iter()
+                .any(|&line| (position.start as usize..=position.end as usize).contains(&line))
+        })
+        .map(|(ix, _)| ix)
+        .collect()
+}
+
+/// Resets the position of every node to `LineSpan::default()`, to compare trees without them.
+#[cfg(test)]
+pub(crate) fn clear_positions(mut nodes: Vec<Node>) -> Vec<Node> {
+    for node in &mut nodes {
+        for_each_position_mut(node, &mut |position| *position = LineSpan::default());
+    }
+    nodes
+}
+
+/// Calls `f` with the position of the node and of every node nested in it.
+pub(crate) fn for_each_position_mut(node: &mut Node, f: &mut impl FnMut(&mut LineSpan)) {
+    let (position, inner, children): (_, &mut [Node], &mut [Node]) = match node {
+        Node::Header(header) => (&mut header.position, &mut header.nodes, &mut []),
+        Node::Paragraph(paragraph) => (&mut paragraph.position, &mut paragraph.nodes, &mut []),
+        Node::UnorderedList(unordered_list) => (
+            &mut unordered_list.position,
+            &mut unordered_list.nodes,
+            &mut unordered_list.children,
+        ),
+        Node::OrderedList(ordered_list) => (
+            &mut ordered_list.position,
+            &mut ordered_list.nodes,
+            &mut ordered_list.children,
+        ),
+        Node::List(list) => (&mut list.position, &mut list.items, &mut []),
+        Node::ListItem(list_item) => (
+            &mut list_item.position,
+            &mut list_item.nodes,
+            &mut list_item.children,
+        ),
+        Node::CodeBlock(code_block) => (&mut code_block.position, &mut [], &mut []),
+        Node::TableOfContents(table_of_contents) => {
+            (&mut table_of_contents.position, &mut [], &mut [])
+        }
+        Node::FrontMatter(front_matter) => (&mut front_matter.position, &mut [], &mut []),
+        Node::Table(table) => {
+            // The cells are not nested nodes, so they are visited here
+            for cell in table
+                .headers
+                .iter_mut()
+                .chain(table.rows.iter_mut().flatten())
+            {
+                for node in cell {
+                    for_each_position_mut(node, f);
+                }
+            }
+            (&mut table.position, &mut [], &mut [])
+        }
+        Node::FootnoteDef(footnote_def) => {
+            (&mut footnote_def.position, &mut footnote_def.nodes, &mut [])
+        }
+        Node::Text(text) => (&mut text.position, &mut [], &mut []),
+        Node::Italic(italic) => (&mut italic.position, &mut italic.nodes, &mut []),
+        Node::Bold(bold) => (&mut bold.position, &mut bold.nodes, &mut []),
+        Node::Strikethrough(strikethrough) => (
+            &mut strikethrough.position,
+            &mut strikethrough.nodes,
+            &mut [],
+        ),
+        Node::InlineCode(inline_code) => (&mut inline_code.position, &mut [], &mut []),
+        Node::Link(link) => (&mut link.position, &mut link.text, &mut []),
+        Node::Image(image) => (&mut image.position, &mut [], &mut []),
+        Node::FootnoteRef(footnote_ref) => (&mut footnote_ref.position, &mut [], &mut []),
+        Node::Whitespace(whitespace) => (&mut whitespace.position, &mut [], &mut []),
+        Node::HardBreak(hard_break) => (&mut hard_break.position, &mut [], &mut []),
+        Node::Alert(alert) => (&mut alert.position, &mut alert.nodes, &mut []),
+        Node::Eol(eol) => (&mut eol.position, &mut [], &mut []),
+    };
+
+    f(position);
+    for node in inner.iter_mut().chain(children.iter_mut()) {
+        for_each_position_mut(node, f);
+    }
+}
+
+/// Visits the nodes of a tree, one method per node type.
+///
+/// The default methods recurse into nested `nodes` and list `children`,
+/// so an implementer only needs to override the methods it cares about.
+/// Call `walk` from an overridden method to keep visiting the descendants.
+pub trait Visitor {
+    fn visit_header(&mut self, header: &Header) {
+        walk(&header.nodes, self);
+    }
+
+    fn visit_paragraph(&mut self, paragraph: &Paragraph) {
+        walk(&paragraph.nodes, self);
+    }
+
+    fn visit_unordered_list(&mut self, unordered_list: &UnorderedList) {
+        walk(&unordered_list.nodes, self);
+        walk(&unordered_list.children, self);
+    }
+
+    fn visit_ordered_list(&mut self, ordered_list: &OrderedList) {
+        walk(&ordered_list.nodes, self);
+        walk(&ordered_list.children, self);
+    }
+
+    fn visit_list(&mut self, list: &List) {
+        walk(&list.items, self);
+    }
+
+    fn visit_list_item(&mut self, list_item: &ListItem) {
+        walk(&list_item.nodes, self);
+        walk(&list_item.children, self);
+    }
+
+    fn visit_code_block(&mut self, _code_block: &CodeBlock) {}
+
+    fn visit_table_of_contents(&mut self, _table_of_contents: &TableOfContents) {}
+
+    fn visit_front_matter(&mut self, _front_matter: &FrontMatter) {}
+
+    fn visit_table(&mut self, table: &Table) {
+        for cell in table.headers.iter().chain(table.rows.iter().flatten()) {
+            walk(cell, self);
+        }
+    }
+
+    fn visit_footnote_def(&mut self, footnote_def: &FootnoteDef) {
+        walk(&footnote_def.nodes, self);
+    }
+
+    fn visit_text(&mut self, _text: &Text) {}
+
+    fn visit_italic(&mut self, italic: &Italic) {
+        walk(&italic.nodes, self);
+    }
+
+    fn visit_bold(&mut self, bold: &Bold) {
+        walk(&bold.nodes, self);
+    }
+
+    fn visit_strikethrough(&mut self, strikethrough: &Strikethrough) {
+        walk(&strikethrough.nodes, self);
+    }
+
+    fn visit_inline_code(&mut self, _inline_code: &InlineCode) {}
+
+    fn visit_link(&mut self, link: &Link) {
+        walk(&link.text, self);
+    }
+
+    fn visit_image(&mut self, _image: &Image) {}
+
+    fn visit_footnote_ref(&mut self, _footnote_ref: &FootnoteRef) {}
+
+    fn visit_whitespace(&mut self, _whitespace: &Whitespace) {}
+
+    fn visit_hard_break(&mut self, _hard_break: &HardBreak) {}
+
+    fn visit_alert(&mut self, alert: &Alert) {
+        walk(&alert.nodes, self);
+    }
+
+    fn visit_eol(&mut self, _eol: &Eol) {}
+}
+
+/// Calls the matching `Visitor` method for every node, in document order.
+pub fn walk(nodes: &[Node], visitor: &mut (impl Visitor + ?Sized)) {
+    for node in nodes {
+        match node {
+            Node::Header(header) => visitor.visit_header(header),
+            Node::Paragraph(paragraph) => visitor.visit_paragraph(paragraph),
+            Node::UnorderedList(unordered_list) => visitor.visit_unordered_list(unordered_list),
+            Node::OrderedList(ordered_list) => visitor.visit_ordered_list(ordered_list),
+            Node::List(list) => visitor.visit_list(list),
+            Node::ListItem(list_item) => visitor.visit_list_item(list_item),
+            Node::CodeBlock(code_block) => visitor.visit_code_block(code_block),
+            Node::TableOfContents(table_of_contents) => {
+                visitor.visit_table_of_contents(table_of_contents)
+            }
+            Node::FrontMatter(front_matter) => visitor.visit_front_matter(front_matter),
+            Node::Table(table) => visitor.visit_table(table),
+            Node::FootnoteDef(footnote_def) => visitor.visit_footnote_def(footnote_def),
+            Node::Text(text) => visitor.visit_text(text),
+            Node::Italic(italic) => visitor.visit_italic(italic),
+            Node::Bold(bold) => visitor.visit_bold(bold),
+            Node::Strikethrough(strikethrough) => visitor.visit_strikethrough(strikethrough),
+            Node::InlineCode(inline_code) => visitor.visit_inline_code(inline_code),
+            Node::Link(link) => visitor.visit_link(link),
+            Node::Image(image) => visitor.visit_image(image),
+            Node::FootnoteRef(footnote_ref) => visitor.visit_footnote_ref(footnote_ref),
+            Node::Whitespace(whitespace) => visitor.visit_whitespace(whitespace),
+            Node::HardBreak(hard_break) => visitor.visit_hard_break(hard_break),
+            Node::Alert(alert) => visitor.visit_alert(alert),
+            Node::Eol(eol) => visitor.visit_eol(eol),
+        }
+    }
 }
 
 pub trait Positioned {
     fn position(&self) -> &LineSpan;
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
+/// The lines and columns a node covers in the original document.
+///
+/// Lines are 1-based and inclusive. Columns are 1-based character columns;
+/// `end_col` is the column just after the last character on the `end` line.
+/// `bytes` holds the same range as byte offsets into the document.
+///
+/// The fields are `u32`s, which keeps the span at half the size it would be with `usize`s,
+/// since every node of the tree holds one. Positions past `u32::MAX`, in documents over 4 GiB,
+/// saturate at `u32::MAX` instead of wrapping around.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Default)]
 pub struct LineSpan {
-    pub start: usize,
-    pub end: usize,
+    pub start: u32,
+    pub end: u32,
+    pub start_col: u32,
+    pub end_col: u32,
+    pub bytes: ByteSpan,
+}
+
+/// A range of byte offsets into the original document.
+///
+/// The offsets always fall on character boundaries, so `&input[span.range()]`
+/// is the source text of the node.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Default)]
+pub struct ByteSpan {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl ByteSpan {
+    /// Returns the offsets as a range that can index the document.
+    pub fn range(&self) -> Range<usize> {
+        self.start as usize..self.end as usize
+    }
 }
 
 macro_rules! impl_positioned {
@@ -53,58 +954,199 @@ macro_rules! impl_positioned {
 impl_positioned!(Header);
 impl_positioned!(Paragraph);
 impl_positioned!(UnorderedList);
+impl_positioned!(OrderedList);
+impl_positioned!(List);
+impl_positioned!(ListItem);
+impl_positioned!(CodeBlock);
+impl_positioned!(TableOfContents);
+impl_positioned!(FrontMatter);
+impl_positioned!(Table);
+impl_positioned!(FootnoteDef);
 impl_positioned!(Text);
 impl_positioned!(Italic);
 impl_positioned!(Bold);
+impl_positioned!(Strikethrough);
+impl_positioned!(InlineCode);
+impl_positioned!(Link);
+impl_positioned!(Image);
+impl_positioned!(FootnoteRef);
 impl_positioned!(Whitespace);
+impl_positioned!(HardBreak);
 impl_positioned!(Alert);
 impl_positioned!(Eol);
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub struct Header {
     pub level: usize,
     pub nodes: Vec<Node>,
     pub position: LineSpan,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub struct Paragraph {
     pub nodes: Vec<Node>,
     pub position: LineSpan,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub struct UnorderedList {
+    pub level: usize,          // 0 for root
+    pub checked: Option<bool>, // the state of a `[ ]` or `[x]` checkbox, for task list items
+    pub nodes: Vec<Node>,
+    pub children: Vec<Node>,
+    pub position: LineSpan,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct OrderedList {
     pub level: usize, // 0 for root
+    pub start: usize, // number written on the item
     pub nodes: Vec<Node>,
     pub children: Vec<Node>,
     pub position: LineSpan,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
+/// A run of sibling list items of the same kind, produced with `Parser::group_lists`.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct List {
+    pub ordered: bool,
+    pub start: usize,     // the number of the first item, 1 for unordered lists
+    pub loose: bool,      // whether an item continues with a paragraph after a blank line
+    pub items: Vec<Node>, // `ListItem` nodes
+    pub position: LineSpan,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct ListItem {
+    pub checked: Option<bool>, // the state of a task list checkbox
+    pub nodes: Vec<Node>,
+    pub children: Vec<Node>, // nested lists, grouped as well
+    pub position: LineSpan,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct CodeBlock {
+    pub language: Option<String>,
+    pub value: String, // content lines joined with '\n', kept verbatim
+    pub position: LineSpan,
+}
+
+/// A list of the document's headers, in place of a `[TOC]` marker.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct TableOfContents {
+    pub entries: Vec<HeadingEntry>,
+    pub position: LineSpan,
+}
+
+/// The `---` delimited block at the start of a document, usually YAML metadata.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct FrontMatter {
+    pub raw: String, // the lines between the fences, not interpreted
+    pub position: LineSpan,
+}
+
+/// A table of `|` separated cells, with a header row and a delimiter row below it.
+///
+/// Every body row has as many cells as the header row.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct Table {
+    pub headers: Vec<Vec<Node>>,
+    pub alignments: Vec<Alignment>, // one per column, from the `:` in the delimiter row
+    pub rows: Vec<Vec<Vec<Node>>>,
+    pub position: LineSpan,
+}
+
+/// The alignment of a table column: `---` is `None`, `:--` `Left`, `:-:` `Center` and `--:` `Right`.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+pub enum Alignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+/// A `[^id]: text` line defining the note that `[^id]` references point to.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct FootnoteDef {
+    pub id: String, // the id as written, without `^`
+    pub nodes: Vec<Node>,
+    pub position: LineSpan,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct HeadingEntry {
+    pub level: usize,
+    pub text: String,   // the header text without formatting
+    pub anchor: String, // the `id` the header gets with `RenderOptions::header_anchors`
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub struct Text {
     pub value: String,
     pub position: LineSpan,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub struct Italic {
     pub nodes: Vec<Node>,
     pub position: LineSpan,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub struct Bold {
     pub nodes: Vec<Node>,
     pub position: LineSpan,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct Strikethrough {
+    pub nodes: Vec<Node>,
+    pub position: LineSpan,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct InlineCode {
+    pub value: String,
+    pub position: LineSpan,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct Link {
+    pub text: Vec<Node>,
+    pub url: String,
+    pub title: Option<String>,
+    pub position: LineSpan,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct Image {
+    pub alt: String, // the alt text without formatting
+    pub src: String,
+    pub title: Option<String>,
+    pub position: LineSpan,
+}
+
+/// A `[^id]` reference to a footnote defined elsewhere in the document.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct FootnoteRef {
+    pub id: String, // the id as written, without `^`
+    pub position: LineSpan,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub struct Whitespace {
     pub position: LineSpan,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Clone)]
+/// A line break inside a paragraph, marked by two trailing spaces or a backslash.
+///
+/// The position covers the marker at the end of the line.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub struct HardBreak {
+    pub position: LineSpan,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub enum AlertType {
     Note,
     Tip,
@@ -113,14 +1155,463 @@ pub enum AlertType {
     Caution,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub struct Alert {
     pub alert_type: AlertType,
     pub nodes: Vec<Node>,
     pub position: LineSpan,
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub struct Eol {
     pub position: LineSpan,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{build_tree, Parser};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_positions_of_header() {
+        let input = "# Title\ntext\n## Section\n- item\n### Sub section";
+        let nodes = build_tree(input);
+
+        assert_eq!(
+            positions_of(&nodes, NodeKind::Header),
+            vec![
+                LineSpan {
+                    start: 1,
+                    end: 1,
+                    start_col: 1,
+                    end_col: 8,
+                    bytes: ByteSpan { start: 0, end: 7 }
+                },
+                LineSpan {
+                    start: 3,
+                    end: 3,
+                    start_col: 1,
+                    end_col: 11,
+                    bytes: ByteSpan { start: 13, end: 23 }
+                },
+                LineSpan {
+                    start: 5,
+                    end: 5,
+                    start_col: 1,
+                    end_col: 16,
+                    bytes: ByteSpan { start: 31, end: 46 }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line_span_is_packed() {
+        // Six `usize` fields would take 48 bytes on a 64-bit target
+        assert_eq!(std::mem::size_of::<LineSpan>(), 24);
+    }
+
+    #[test]
+    fn test_span_offset_saturates() {
+        assert_eq!(span_offset(7), 7);
+        assert_eq!(span_offset(u32::MAX as usize), u32::MAX);
+        assert_eq!(span_offset(usize::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn test_walk_with_depth() {
+        let input = "- item\n - nested";
+        let nodes = build_tree(input);
+
+        let mut depths = vec![];
+        walk_with_depth(&nodes, |depth, node| depths.push((depth, node.kind())));
+
+        assert_eq!(
+            depths,
+            vec![
+                (0, NodeKind::UnorderedList),
+                (1, NodeKind::Text),
+                (1, NodeKind::UnorderedList),
+                (2, NodeKind::Text),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_visitor_counts_text_in_nested_list() {
+        struct TextCounter {
+            count: usize,
+        }
+
+        impl Visitor for TextCounter {
+            fn visit_text(&mut self, _text: &Text) {
+                self.count += 1;
+            }
+        }
+
+        let input = "- item 1\n - item 1.1\n  - item 1.1.1";
+        let nodes = build_tree(input);
+
+        let mut counter = TextCounter { count: 0 };
+        walk(&nodes, &mut counter);
+
+        assert_eq!(counter.count, 6);
+    }
+
+    #[test]
+    fn test_clone_nested_list() {
+        let input = "- item 1\n - item 1.1\n  - item 1.1.1";
+        let nodes = build_tree(input);
+
+        let cloned = nodes[0].clone();
+
+        assert_eq!(cloned, nodes[0]);
+    }
+
+    #[test]
+    fn test_clone_offset() {
+        let input = "- item\n - nested";
+        let nodes = build_tree(input);
+
+        let shifted = clone_offset(&nodes[0], 10);
+
+        let mut lines = vec![];
+        walk_with_depth(std::slice::from_ref(&shifted), |_, node| {
+            lines.push((node.position().start, node.position().end))
+        });
+        assert_eq!(lines, vec![(11, 12), (11, 11), (12, 12), (12, 12)]);
+        assert_eq!(shifted.position().start_col, nodes[0].position().start_col);
+        assert_eq!(clone_offset(&nodes[0], -10).position().start, 0);
+    }
+
+    #[test]
+    fn test_insert_at() {
+        let mut nodes = build_tree("first\nlast");
+        let fragment = build_tree("*inserted*");
+
+        insert_at(&mut nodes, 2, fragment);
+
+        assert_eq!(nodes, build_tree("first\n*inserted*\nlast"));
+    }
+
+    #[test]
+    fn test_to_plain_text() {
+        let input =
+            "# Title *here*\n**text** with `code`\n\n- item 1\n - item 1.1\n- item 2\n\n[end](url)";
+        let nodes = build_tree(input);
+
+        assert_eq!(
+            to_plain_text(&nodes),
+            "Title here\ntext with code\n\nitem 1\nitem 1.1\nitem 2\n\nend"
+        );
+        assert_eq!(nodes[0].to_string(), "Title here");
+    }
+
+    #[test]
+    fn test_text_content() {
+        let nodes = build_tree("# Title *here*\n- item 1\n - item 1.1\n*some **bold** text* after");
+
+        assert_eq!(nodes[0].text_content(), "Title here");
+        assert_eq!(nodes[1].text_content(), "item 1 item 1.1");
+        let Node::Paragraph(paragraph) = &nodes[2] else {
+            panic!("expected a paragraph, got {:?}", nodes[2]);
+        };
+        assert_eq!(paragraph.nodes[0].text_content(), "some bold text");
+    }
+
+    #[test]
+    fn test_text_content_of_multi_line_setext_header() {
+        let nodes = build_tree("a\nb\n===");
+
+        assert_eq!(nodes[0].text_content(), "a b");
+    }
+
+    #[test]
+    fn test_sort_nodes() {
+        let sorted = build_tree("# Title\ntext\n- item\n- other\n\nlast");
+        let mut nodes = sorted.clone();
+        nodes.reverse();
+        nodes.swap(0, 2);
+
+        sort_nodes(&mut nodes);
+
+        assert_eq!(nodes, sorted);
+    }
+
+    #[test]
+    fn test_flatten() {
+        let nodes = build_tree("# *Title*\n- item\n - nested\n- next");
+
+        let kinds: Vec<NodeKind> = flatten(&nodes).map(|node| node.kind()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                NodeKind::Header,
+                NodeKind::Italic,
+                NodeKind::Text,
+                NodeKind::UnorderedList,
+                NodeKind::Text,
+                NodeKind::UnorderedList,
+                NodeKind::Text,
+                NodeKind::UnorderedList,
+                NodeKind::Text,
+            ]
+        );
+
+        let kinds: Vec<NodeKind> = nodes[1].descendants().map(|node| node.kind()).collect();
+        assert_eq!(
+            kinds,
+            vec![NodeKind::Text, NodeKind::UnorderedList, NodeKind::Text]
+        );
+    }
+
+    #[test]
+    fn test_table_cells_are_nested() {
+        let nodes = build_tree("text\n| a | *b* |\n| - | - |\n| c | d |");
+
+        assert_eq!(nodes[1].text_content(), "a b c d");
+        assert_eq!(
+            positions_of(&nodes, NodeKind::Italic),
+            vec![LineSpan {
+                start: 2,
+                end: 2,
+                start_col: 7,
+                end_col: 10,
+                bytes: ByteSpan { start: 11, end: 14 }
+            }]
+        );
+        let Some(Node::Text(text)) = node_at_line(&nodes, 4) else {
+            panic!("expected the text of a cell on line 4");
+        };
+        assert_eq!(text.value, "c");
+        assert_eq!(summary(&nodes).words, 5);
+        assert_eq!(node_ids(&nodes).len(), flatten(&nodes).count());
+    }
+
+    #[test]
+    fn test_line_hashes() {
+        let old = "# Title\ntext\n- item\n- other";
+        let new = "# Title\nchanged text\n- item\n- other";
+        let old_hashes = line_hashes(old);
+        let new_hashes = line_hashes(new);
+
+        assert_eq!(old_hashes.len(), 4);
+        assert_eq!(old_hashes[0], new_hashes[0]);
+        assert_ne!(old_hashes[1], new_hashes[1]);
+        assert_eq!(old_hashes[2..], new_hashes[2..]);
+        assert_eq!(
+            affected_blocks(&build_tree(new), &old_hashes, &new_hashes),
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let input = "- item *1*\n - item 1.1\n  1. item 1.1.1\n- [x] item 2";
+        let nodes = build_tree(input);
+
+        let json = serde_json::to_string(&nodes).unwrap();
+        let restored: Vec<Node> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored, nodes);
+    }
+
+    #[test]
+    fn test_to_value() {
+        let nodes = build_tree("# Title\ntext");
+        let value = to_value(&nodes).unwrap();
+
+        let blocks = value.as_array().unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks.iter().all(|block| block.is_object()));
+        assert_eq!(blocks[0]["Header"]["level"], 1);
+        assert_eq!(blocks[1]["Paragraph"]["nodes"][0]["Text"]["value"], "text");
+    }
+
+    #[test]
+    fn test_to_outline_json() {
+        let nodes = build_tree("# Intro\n## Setup\n### Linux\n## *Usage*\n# Next");
+
+        assert_eq!(
+            to_outline_json(&nodes),
+            serde_json::json!([
+                {
+                    "level": 1,
+                    "text": "Intro",
+                    "children": [
+                        {
+                            "level": 2,
+                            "text": "Setup",
+                            "children": [{ "level": 3, "text": "Linux", "children": [] }]
+                        },
+                        { "level": 2, "text": "Usage", "children": [] }
+                    ]
+                },
+                { "level": 1, "text": "Next", "children": [] }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_summary() {
+        let input = "# Title\n\nSome intro text.\n\n## Section\n- one item\n- two\n - nested\n\n### Deep\nlast *emphasized* words";
+        let nodes = build_tree(input);
+
+        assert_eq!(
+            summary(&nodes),
+            DocumentSummary {
+                headers: [1, 1, 1, 0, 0, 0],
+                paragraphs: 2,
+                list_items: 3,
+                words: 13,
+                last_line: 11,
+            }
+        );
+        let trailing = format!("{input}\n\n");
+        assert_eq!(summary(&build_tree(&trailing)).last_line, 11);
+    }
+
+    #[test]
+    fn test_document_span() {
+        assert_eq!(document_span(&[]), LineSpan::default());
+        assert_eq!(
+            document_span(&build_tree("one *line*")),
+            LineSpan {
+                start: 1,
+                end: 1,
+                start_col: 1,
+                end_col: 11,
+                bytes: ByteSpan { start: 0, end: 10 }
+            }
+        );
+        assert_eq!(
+            document_span(&build_tree(
+                "# Title
+
+- item
+ - nested
+
+last"
+            )),
+            LineSpan {
+                start: 1,
+                end: 6,
+                start_col: 1,
+                end_col: 5,
+                bytes: ByteSpan { start: 0, end: 31 }
+            }
+        );
+    }
+
+    #[test]
+    fn test_node_ids() {
+        let input = "# Title\n- item\n - nested";
+        let first = build_tree(input);
+        let second = build_tree(input);
+
+        let ids: Vec<(NodeId, NodeKind)> = node_ids(&first)
+            .into_iter()
+            .map(|(id, node)| (id, node.kind()))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                (NodeId(0), NodeKind::Header),
+                (NodeId(1), NodeKind::Text),
+                (NodeId(2), NodeKind::UnorderedList),
+                (NodeId(3), NodeKind::Text),
+                (NodeId(4), NodeKind::UnorderedList),
+                (NodeId(5), NodeKind::Text),
+            ]
+        );
+        assert_eq!(node_ids(&first), node_ids(&second));
+    }
+
+    #[test]
+    fn test_node_at_line() {
+        let input = "# Title\n\n- first\n - nested\n- second";
+        let nodes = build_tree(input);
+
+        assert_eq!(
+            node_at_line(&nodes, 1),
+            Some(&Node::Text(Text {
+                value: "Title".to_string(),
+                position: LineSpan {
+                    start: 1,
+                    end: 1,
+                    start_col: 3,
+                    end_col: 8,
+                    bytes: ByteSpan { start: 2, end: 7 }
+                }
+            }))
+        );
+        assert_eq!(
+            node_at_line(&nodes, 2),
+            Some(&Node::Eol(Eol {
+                position: LineSpan {
+                    start: 2,
+                    end: 2,
+                    start_col: 1,
+                    end_col: 2,
+                    bytes: ByteSpan { start: 8, end: 9 }
+                }
+            }))
+        );
+        assert_eq!(
+            node_at_line(&nodes, 4),
+            Some(&Node::Text(Text {
+                value: "nested".to_string(),
+                position: LineSpan {
+                    start: 4,
+                    end: 4,
+                    start_col: 4,
+                    end_col: 10,
+                    bytes: ByteSpan { start: 20, end: 26 }
+                }
+            }))
+        );
+        assert_eq!(
+            node_at_line(&nodes, 5).map(Node::kind),
+            Some(NodeKind::Text)
+        );
+        assert_eq!(node_at_line(&nodes, 6), None);
+    }
+
+    #[test]
+    fn test_raw() {
+        let input = "# Title\nHello, *world*! (see `x`)\n- item\n - nested";
+        let nodes = build_tree(input);
+
+        assert_eq!(nodes[0].raw(input), Some("# Title"));
+        assert_eq!(nodes[1].raw(input), Some("Hello, *world*! (see `x`)"));
+        assert_eq!(nodes[2].raw(input), Some("- item\n - nested"));
+
+        let nodes = clear_positions(Parser::new().parse(input));
+        assert_eq!(nodes[1].raw(input), None);
+    }
+
+    #[test]
+    fn test_debug_tree() {
+        let input = "# Title\n*italic* and `some longer inline code`\n- item\n - nested";
+        let nodes = build_tree(input);
+
+        assert_eq!(
+            debug_tree(&nodes),
+            "Header 1:1-1:8
+  Text 1:3-1:8 \"Title\"
+Paragraph 2:1-2:39
+  Italic 2:1-2:9
+    Text 2:2-2:8 \"italic\"
+  Whitespace 2:9-2:10
+  Text 2:10-2:13 \"and\"
+  Whitespace 2:13-2:14
+  InlineCode 2:14-2:39 \"some longer inline c\"...
+UnorderedList 3:1-4:10
+  Text 3:3-3:7 \"item\"
+  UnorderedList 4:2-4:10
+    Text 4:4-4:10 \"nested\"
+"
+        );
+    }
+}